@@ -1,7 +1,3 @@
-use std::{cell::RefCell, rc::Rc};
-
-use crate::{name_resolution::symbol_table::SymbolTable, scanner::token::Token};
-
 use super::{ walker::AstWalker, Node};
 
 // Provide methods to print out a formatted AST
@@ -43,16 +39,28 @@ impl AstWalker<String> for AstFormat {
         "EMPTY".to_string()
     }
 
+    fn walk_invalid(&mut self, _inner : &super::Invalid) -> String {
+        "INVALID".to_string()
+    }
+
     fn walk_int(&mut self, inner : &super::Int) -> String {
+        format!("{}{}", inner.val, inner.suffix)
+    }
+
+    fn walk_float(&mut self, inner : &super::Float) -> String {
+        format!("{:?}{}", inner.val, inner.suffix)
+    }
+
+    fn walk_bool(&mut self, inner : &super::Bool) -> String {
         inner.val.to_string()
     }
 
     fn walk_char(&mut self, inner : &super::Char) -> String {
-        format!("'{0}'", inner.val)
+        format!("'{0}'", inner.val.escape_debug())
     }
 
     fn walk_str(&mut self, inner : &super::Str) -> String {
-        format!("\"{0}\"", inner.val)
+        format!("\"{0}\"", inner.val.escape_debug())
     }
 
     fn walk_array(&mut self, inner : &super::Array) -> String {
@@ -63,6 +71,13 @@ impl AstWalker<String> for AstFormat {
             .collect::<String>())
     }
 
+    fn walk_array_decl(&mut self, inner : &super::ArrayDecl) -> String {
+        let decl = self.walk(&inner.decl);
+        self.last_child = true;
+        let len = self.walk(&inner.len);
+        format!("━ARRAY DECL\n{}\n{}", decl, len)
+    }
+
     fn walk_statement(&mut self, inner : &super::Statement) -> String {
         let expr = self.walk(&inner.expr);
         self.last_child = true;
@@ -86,6 +101,13 @@ impl AstWalker<String> for AstFormat {
         format!("━{:?}\n{}\n{}",inner.op_type, lhs, rhs)
     }
 
+    fn walk_logical(&mut self, inner : &super::Logical) -> String {
+        let lhs = self.walk(&inner.lhs);
+        self.last_child = true;
+        let rhs = self.walk(&inner.rhs);
+        format!("━{:?}\n{}\n{}",inner.op_type, lhs, rhs)
+    }
+
     fn walk_prefix(&mut self, inner : &super::PrefixOp) -> String {
         self.last_child = true;
         format!("━{:?}\n{}", inner.op_type, self.walk(&inner.rhs))
@@ -105,6 +127,172 @@ impl AstWalker<String> for AstFormat {
             .collect::<String>())
     }
 
+    fn walk_funct_decl(&mut self, inner : &super::FunctDecl) -> String {
+        format!("━FUNCTION DECL {} : {}{}\n{}", 
+            inner.name,
+            inner.ret_type
+            .iter()
+            .map(|x| format!("{x:?}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+            inner.params
+            .iter()
+            .map(|x| format!("\n{}", self.walk(&x.decl)))
+            .collect::<String>(),
+            {
+                self.last_child = true;
+                self.walk(&inner.body)
+            })
+    }
+
+    fn walk_lambda(&mut self, inner : &super::Lambda) -> String {
+        format!("━LAMBDA : {}{}\n{}", 
+            inner.ret_type
+            .iter()
+            .map(|x| format!("{x:?}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+            inner.params
+            .iter()
+            .map(|x| format!("\n{}", self.walk(&x.decl)))
+            .collect::<String>(),
+            {
+                self.last_child = true;
+                self.walk(&inner.body)
+            })
+    }
+
+    fn walk_struct_decl(&mut self, inner : &super::StructDecl) -> String {
+        format!("━STRUCT {}{}", 
+            inner.name,
+            inner.fields
+            .iter()
+            .map(|x| format!("\n{}", self.walk(x)))
+            .collect::<String>())
+    }
+
+    // Each field is printed as a child, with its value below it
+    fn walk_struct_lit(&mut self, inner : &super::StructLit) -> String {
+        let mut s = format!("━STRUCT LITERAL {}", inner.name);
+        for (i, field) in inner.fields.iter().enumerate() {
+            let last = i + 1 == inner.fields.len();
+            s.push_str(&format!("\n{}{}━{}\n", self.prefix_stack, if last {'┗'} else {'┣'}, field.name));
+            self.prefix_stack.push_str(if last {"   "} else {"┃  "});
+            self.last_child = true;
+            s.push_str(&self.walk(&field.val));
+            for _ in 0..3 {
+                self.prefix_stack.pop();
+            }
+        }
+        s
+    }
+
+    fn walk_member(&mut self, inner : &super::Member) -> String {
+        self.last_child = true;
+        let offset = inner.offset.map(|o| format!(" (offset {o})")).unwrap_or_default();
+        format!("━{:?} {}{}\n{}", inner.op_type, inner.field, offset, self.walk(&inner.lhs))
+    }
+
+    // Variants have no children, so each is printed on a single line
+    fn walk_enum_decl(&mut self, inner : &super::EnumDecl) -> String {
+        let mut s = format!("━ENUM {}", inner.name);
+        for (i, variant) in inner.variants.iter().enumerate() {
+            let last = i + 1 == inner.variants.len();
+            s.push_str(&format!("\n{}{}━{}", self.prefix_stack, if last {'┗'} else {'┣'}, variant.name));
+            if !variant.fields.is_empty() {
+                s.push_str(&format!("({})", variant.fields
+                    .iter()
+                    .map(fmt_type_name)
+                    .collect::<Vec<_>>()
+                    .join(", ")));
+            }
+        }
+        s
+    }
+
+    // Each arm is printed as a child, with its body below it
+    fn walk_match(&mut self, inner : &super::Match) -> String {
+        self.last_child = inner.arms.is_empty();
+        let mut s = format!("━MATCH\n{}", self.walk(&inner.val));
+        for (i, arm) in inner.arms.iter().enumerate() {
+            let last = i + 1 == inner.arms.len();
+            let pattern = arm.variant.clone().unwrap_or("_".to_string());
+            let tag = arm.tag.map(|t| format!(" (tag {t})")).unwrap_or_default();
+            let bindings = match arm.bindings.is_empty() {
+                true => String::new(),
+                false => format!("({})", arm.bindings.join(", ")),
+            };
+            s.push_str(&format!("\n{}{}━{}{}{} =>\n", self.prefix_stack, if last {'┗'} else {'┣'}, pattern, bindings, tag));
+            self.prefix_stack.push_str(if last {"   "} else {"┃  "});
+            self.last_child = true;
+            s.push_str(&self.walk(&arm.body));
+            for _ in 0..3 {
+                self.prefix_stack.pop();
+            }
+        }
+        s
+    }
+
+    // Each arm is printed as a child, with its cases and then its body below it
+    fn walk_switch(&mut self, inner : &super::Switch) -> String {
+        self.last_child = inner.arms.is_empty();
+        // The range of values the jump table covers
+        let table = match &inner.table {
+            Some(t) => format!(" JUMP TABLE {}..={}", t.low, t.low.saturating_add(t.arms.len() as i64 - 1)),
+            None => String::new(),
+        };
+        let mut s = format!("━SWITCH{}\n{}", table, self.walk(&inner.val));
+        for (i, arm) in inner.arms.iter().enumerate() {
+            let last = i + 1 == inner.arms.len();
+            let label = if arm.cases.is_some() {"CASE"} else {"DEFAULT"};
+            s.push_str(&format!("\n{}{}━{}", self.prefix_stack, if last {'┗'} else {'┣'}, label));
+            self.prefix_stack.push_str(if last {"   "} else {"┃  "});
+            for case in arm.cases.iter().flatten() {
+                s.push_str(&format!("\n{}", self.walk(case)));
+            }
+            self.last_child = true;
+            s.push_str(&format!("\n{}", self.walk(&arm.body)));
+            for _ in 0..3 {
+                self.prefix_stack.pop();
+            }
+        }
+        s
+    }
+
+    fn walk_import(&mut self, inner : &super::Import) -> String {
+        format!("━IMPORT {}", inner.path)
+    }
+
+    fn walk_typedef(&mut self, inner : &super::TypeDef) -> String {
+        match &inner.aliased {
+            super::Aliased::Decl(decl) => {
+                self.last_child = true;
+                format!("━TYPEDEF {}\n{}", inner.name, self.walk(decl))
+            },
+            super::Aliased::TypeName(type_name) => format!("━TYPE {} = {}", inner.name, fmt_type_name(type_name)),
+        }
+    }
+
+    // Once the program has been resolved, the size is printed after the operand
+    fn walk_sizeof(&mut self, inner : &super::Sizeof) -> String {
+        let size = match inner.size {
+            Some(size) => format!(" = {size}"),
+            None => String::new(),
+        };
+        match &inner.arg {
+            super::SizeofArg::TypeName(type_name) => format!("━SIZEOF {}{}", fmt_type_name(type_name), size),
+            super::SizeofArg::Expr(expr) => {
+                self.last_child = true;
+                format!("━SIZEOF{}\n{}", size, self.walk(expr))
+            },
+        }
+    }
+
+    fn walk_cast(&mut self, inner : &super::Cast) -> String {
+        self.last_child = true;
+        format!("━CAST {}\n{}", fmt_type_name(&inner.type_name), self.walk(&inner.expr))
+    }
+
     fn walk_if(&mut self, inner : &super::If) -> String {
         let c = self.walk(&inner.cond);
         let t = self.walk(&inner.t_expr);
@@ -113,5 +301,46 @@ impl AstWalker<String> for AstFormat {
         format!("━IF\n{}\n{}\n{}", c, t, f)
     }
 
+    fn walk_while(&mut self, inner : &super::While) -> String {
+        let c = self.walk(&inner.cond);
+        self.last_child = true;
+        let b = self.walk(&inner.body);
+        format!("━WHILE{}\n{}\n{}", fmt_label(&inner.label), c, b)
+    }
+
+    fn walk_for(&mut self, inner : &super::For) -> String {
+        let i = self.walk(&inner.init);
+        let c = self.walk(&inner.cond);
+        let s = self.walk(&inner.step);
+        self.last_child = true;
+        let b = self.walk(&inner.body);
+        format!("━FOR{}\n{}\n{}\n{}\n{}", fmt_label(&inner.label), i, c, s, b)
+    }
+
+    fn walk_break(&mut self, inner : &super::Break) -> String {
+        self.last_child = true;
+        format!("━BREAK{}\n{}", fmt_label(&inner.label), self.walk(&inner.val))
+    }
+
+    fn walk_continue(&mut self, inner : &super::Continue) -> String {
+        format!("━CONTINUE{}", fmt_label(&inner.label))
+    }
+
+}
+
+// Format a type name as its type specifiers followed by a star for each level of pointer
+fn fmt_type_name(type_name : &super::TypeName) -> String {
+    format!("{}{}", type_name.specs
+        .iter()
+        .map(|x| format!("{x:?}"))
+        .collect::<Vec<_>>()
+        .join(" "),
+        "*".repeat(type_name.pointers))
 }
 
+fn fmt_label(label : &Option<String>) -> String {
+    match label {
+        Some(l) => format!(" {l}"),
+        None => String::new(),
+    }
+}
@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
 
-use crate::{name_resolution::symbol_table::SymbolTable, scanner::token::Token};
+use crate::{name_resolution::symbol::SymbolTable, scanner::{span::Span, token::{FloatSuffix, FloatVal, IntSuffix, Token}}};
 
 pub mod walker;
 pub mod toucher;
@@ -14,180 +14,652 @@ pub mod format;
 // Node is essentially a box to an ast node that also stores what type the ast node is
 pub enum Node {
     Empty, // A placeholder node which represents the unparsed program or a parsed, but empty, program.
+    Invalid(Box<Invalid>), // A placeholder for an expression which could not be parsed
     Int(Box<Int>),
+    Float(Box<Float>),
+    Bool(Box<Bool>),
     Char(Box<Char>),
     Str(Box<Str>),
     Array(Box<Array>),
+    ArrayDecl(Box<ArrayDecl>),
     Statement(Box<Statement>),
     Block(Box<Block>),
     Id(Box<Id>),
     InfixOp(Box<InfixOp>),
+    Logical(Box<Logical>),
     PrefixOp(Box<PrefixOp>),
     PostfixOp(Box<PostfixOp>),
     Funct(Box<Funct>),
+    FunctDecl(Box<FunctDecl>),
+    Lambda(Box<Lambda>),
+    StructDecl(Box<StructDecl>),
+    StructLit(Box<StructLit>),
+    Member(Box<Member>),
+    EnumDecl(Box<EnumDecl>),
+    Match(Box<Match>),
+    Switch(Box<Switch>),
+    Import(Box<Import>),
+    TypeDef(Box<TypeDef>),
+    Sizeof(Box<Sizeof>),
+    Cast(Box<Cast>),
     If(Box<If>),
+    While(Box<While>),
+    For(Box<For>),
+    Break(Box<Break>),
+    Continue(Box<Continue>),
 }
 
 impl Node {
-    pub fn new_int(lnum : &u64, val : &i32) -> Node {
+    // Return the span of source text this node was parsed from. Empty nodes do not correspond to
+    // any source text
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Node::Empty => None,
+            Node::Invalid(n) => Some(&n.span),
+            Node::Int(n) => Some(&n.span),
+            Node::Float(n) => Some(&n.span),
+            Node::Bool(n) => Some(&n.span),
+            Node::Char(n) => Some(&n.span),
+            Node::Str(n) => Some(&n.span),
+            Node::Array(n) => Some(&n.span),
+            Node::ArrayDecl(n) => Some(&n.span),
+            Node::Statement(n) => Some(&n.span),
+            Node::Block(n) => Some(&n.span),
+            Node::Id(n) => Some(&n.span),
+            Node::InfixOp(n) => Some(&n.span),
+            Node::Logical(n) => Some(&n.span),
+            Node::PrefixOp(n) => Some(&n.span),
+            Node::PostfixOp(n) => Some(&n.span),
+            Node::Funct(n) => Some(&n.span),
+            Node::FunctDecl(n) => Some(&n.span),
+            Node::Lambda(n) => Some(&n.span),
+            Node::StructDecl(n) => Some(&n.span),
+            Node::StructLit(n) => Some(&n.span),
+            Node::Member(n) => Some(&n.span),
+            Node::EnumDecl(n) => Some(&n.span),
+            Node::Match(n) => Some(&n.span),
+            Node::Switch(n) => Some(&n.span),
+            Node::Import(n) => Some(&n.span),
+            Node::TypeDef(n) => Some(&n.span),
+            Node::Sizeof(n) => Some(&n.span),
+            Node::Cast(n) => Some(&n.span),
+            Node::If(n) => Some(&n.span),
+            Node::While(n) => Some(&n.span),
+            Node::For(n) => Some(&n.span),
+            Node::Break(n) => Some(&n.span),
+            Node::Continue(n) => Some(&n.span),
+        }
+    }
+
+    pub fn new_invalid(span : &Span) -> Node {
+        Node::Invalid(Box::new(Invalid {
+            span : span.clone(),
+        }))
+    }
+
+    pub fn new_int(span : &Span, val : &u64, suffix : &IntSuffix, radix : u32) -> Node {
         Node::Int(Box::new(Int {
-            lnum : *lnum, 
+            span : span.clone(), 
+            val : *val,
+            suffix : *suffix,
+            radix : radix,
+        }))
+    }
+
+    pub fn new_float(span : &Span, val : &FloatVal, suffix : &FloatSuffix) -> Node {
+        Node::Float(Box::new(Float {
+            span : span.clone(),
+            val : val.val(),
+            suffix : *suffix,
+        }))
+    }
+
+    pub fn new_bool(span : &Span, val : &bool) -> Node {
+        Node::Bool(Box::new(Bool {
+            span : span.clone(),
             val : *val,
         }))
     }
 
-    pub fn new_char(lnum : &u64, val : &char) -> Node {
+    pub fn new_char(span : &Span, val : &char) -> Node {
         Node::Char(Box::new(Char {
-            lnum : *lnum, 
+            span : span.clone(), 
             val : *val,
         }))
     }
 
-    pub fn new_str(lnum : &u64, val : &str) -> Node {
+    pub fn new_str(span : &Span, val : &str) -> Node {
         Node::Str(Box::new(Str {
-            lnum : *lnum, 
+            span : span.clone(), 
             val : val.to_string(),
         }))
     }
 
-    pub fn new_array(lnum : &u64, val : Vec<Node>) -> Node {
+    pub fn new_array(span : &Span, val : Vec<Node>) -> Node {
         Node::Array(Box::new(Array {
-            lnum : *lnum, 
+            span : span.clone(), 
             val : val,
         }))
     }
 
-    pub fn new_statement(lnum : &u64, expr : Node, next : Node) -> Node {
+    pub fn new_statement(span : &Span, expr : Node, next : Node) -> Node {
         Node::Statement(Box::new(Statement {
-            lnum : *lnum, 
+            span : span.clone(), 
             expr : expr, 
             next : next,
         }))
     }
 
-    pub fn new_block(lnum : &u64, statements : Node, scope : Option<Rc<RefCell<SymbolTable>>>) -> Node {
+    pub fn new_block(span : &Span, statements : Node, scope : Option<Rc<RefCell<SymbolTable>>>) -> Node {
         Node::Block(Box::new(Block {
-            lnum : *lnum,
+            span : span.clone(),
             statements : statements,
             scope : scope,
         }))
     }
 
-    pub fn new_id(lnum : &u64, name : &str) -> Node {
+    pub fn new_id(span : &Span, name : &str) -> Node {
         Node::Id(Box::new(Id {
-            lnum : *lnum, 
+            span : span.clone(), 
             name : name.to_string(),
         }))
     }
 
-    pub fn new_infix(lnum : &u64, op_type : &Token, lhs : Node, rhs : Node) -> Node {
+    pub fn new_infix(span : &Span, op_type : &Token, lhs : Node, rhs : Node) -> Node {
         Node::InfixOp(Box::new(InfixOp {
-            lnum : *lnum,
+            span : span.clone(),
+            op_type : op_type.clone(),
+            lhs : lhs,
+            rhs : rhs,
+        }))
+    }
+
+    pub fn new_logical(span : &Span, op_type : &Token, lhs : Node, rhs : Node) -> Node {
+        Node::Logical(Box::new(Logical {
+            span : span.clone(),
             op_type : op_type.clone(),
             lhs : lhs,
             rhs : rhs,
         }))
     }
 
-    pub fn new_prefix(lnum : &u64, op_type : &Token, rhs : Node) -> Node {
+    pub fn new_prefix(span : &Span, op_type : &Token, rhs : Node) -> Node {
         Node::PrefixOp(Box::new(PrefixOp {
-            lnum : *lnum,
+            span : span.clone(),
             op_type : op_type.clone(),
             rhs : rhs,
         }))
     }
 
-    pub fn new_postfix(lnum : &u64, op_type : &Token, lhs : Node) -> Node {
+    pub fn new_postfix(span : &Span, op_type : &Token, lhs : Node) -> Node {
         Node::PostfixOp(Box::new(PostfixOp {
-            lnum : *lnum,
+            span : span.clone(),
             op_type : op_type.clone(),
             lhs : lhs,
         }))
     }
-    pub fn new_funct(lnum : &u64, name : Node, args : Vec<Node>) -> Node {
+    pub fn new_funct(span : &Span, name : Node, args : Vec<Node>) -> Node {
         Node::Funct(Box::new(Funct {
-            lnum : *lnum,
+            span : span.clone(),
             name : name,
             args : args,
         }))
     }
 
-    pub fn new_if(lnum : &u64, cond : Node, t_expr : Node, f_expr : Node) -> Node {
+    pub fn new_array_decl(span : &Span, decl : Node, len : Node) -> Node {
+        Node::ArrayDecl(Box::new(ArrayDecl {
+            span : span.clone(),
+            decl : decl,
+            len : len,
+        }))
+    }
+
+    pub fn new_funct_decl(span : &Span, name : &str, params : Vec<Param>, ret_type : &[Token], body : Node) -> Node {
+        Node::FunctDecl(Box::new(FunctDecl {
+            span : span.clone(),
+            name : name.to_string(),
+            params : params,
+            ret_type : ret_type.to_vec(),
+            body : body,
+        }))
+    }
+
+    pub fn new_switch(span : &Span, val : Node, arms : Vec<SwitchArm>) -> Node {
+        Node::Switch(Box::new(Switch {
+            span : span.clone(),
+            val : val,
+            arms : arms,
+            table : None,
+        }))
+    }
+
+    pub fn new_lambda(span : &Span, params : Vec<Param>, ret_type : &[Token], body : Node) -> Node {
+        Node::Lambda(Box::new(Lambda {
+            span : span.clone(),
+            params : params,
+            ret_type : ret_type.to_vec(),
+            body : body,
+            captures : Vec::new(),
+        }))
+    }
+
+    pub fn new_struct_decl(span : &Span, name : &str, fields : Vec<Node>) -> Node {
+        Node::StructDecl(Box::new(StructDecl {
+            span : span.clone(),
+            name : name.to_string(),
+            fields : fields,
+        }))
+    }
+
+    pub fn new_struct_lit(span : &Span, name : &str, fields : Vec<FieldInit>) -> Node {
+        Node::StructLit(Box::new(StructLit {
+            span : span.clone(),
+            name : name.to_string(),
+            fields : fields,
+        }))
+    }
+
+    pub fn new_member(span : &Span, op_type : &Token, lhs : Node, field : &str) -> Node {
+        Node::Member(Box::new(Member {
+            span : span.clone(),
+            op_type : op_type.clone(),
+            lhs : lhs,
+            field : field.to_string(),
+            offset : None,
+        }))
+    }
+
+    pub fn new_enum_decl(span : &Span, name : &str, variants : Vec<Variant>) -> Node {
+        Node::EnumDecl(Box::new(EnumDecl {
+            span : span.clone(),
+            name : name.to_string(),
+            variants : variants,
+        }))
+    }
+
+    pub fn new_match(span : &Span, val : Node, arms : Vec<MatchArm>) -> Node {
+        Node::Match(Box::new(Match {
+            span : span.clone(),
+            val : val,
+            arms : arms,
+        }))
+    }
+
+    pub fn new_import(span : &Span, path : &str) -> Node {
+        Node::Import(Box::new(Import {
+            span : span.clone(),
+            path : path.to_string(),
+            module : None,
+        }))
+    }
+
+    pub fn new_typedef(span : &Span, name : &str, aliased : Aliased) -> Node {
+        Node::TypeDef(Box::new(TypeDef {
+            span : span.clone(),
+            name : name.to_string(),
+            aliased : aliased,
+        }))
+    }
+
+    pub fn new_sizeof(span : &Span, arg : SizeofArg) -> Node {
+        Node::Sizeof(Box::new(Sizeof {
+            span : span.clone(),
+            arg : arg,
+            size : None,
+        }))
+    }
+
+    pub fn new_cast(span : &Span, type_name : TypeName, expr : Node) -> Node {
+        Node::Cast(Box::new(Cast {
+            span : span.clone(),
+            type_name : type_name,
+            expr : expr,
+        }))
+    }
+
+    pub fn new_if(span : &Span, cond : Node, t_expr : Node, f_expr : Node) -> Node {
         Node::If(Box::new(If {
-            lnum : *lnum,
+            span : span.clone(),
             cond : cond,
             t_expr : t_expr,
             f_expr : f_expr,
         }))
     }
+
+    pub fn new_while(span : &Span, label : Option<String>, cond : Node, body : Node) -> Node {
+        Node::While(Box::new(While {
+            span : span.clone(),
+            label : label,
+            cond : cond,
+            body : body,
+        }))
+    }
+
+    pub fn new_for(span : &Span, label : Option<String>, init : Node, cond : Node, step : Node, body : Node) -> Node {
+        Node::For(Box::new(For {
+            span : span.clone(),
+            label : label,
+            init : init,
+            cond : cond,
+            step : step,
+            body : body,
+        }))
+    }
+
+    pub fn new_break(span : &Span, label : Option<String>, val : Node) -> Node {
+        Node::Break(Box::new(Break {
+            span : span.clone(),
+            label : label,
+            val : val,
+        }))
+    }
+
+    pub fn new_continue(span : &Span, label : Option<String>) -> Node {
+        Node::Continue(Box::new(Continue {
+            span : span.clone(),
+            label : label,
+        }))
+    }
+}
+
+pub struct Invalid {
+    pub span : Span,
 }
 
 pub struct Int {
-    pub lnum : u64,
-    pub val : i32,
+    pub span : Span,
+    pub val : u64,
+    pub suffix : IntSuffix,
+    pub radix : u32, // The radix the constant was written in, which affects its type
+}
+
+pub struct Float {
+    pub span : Span,
+    pub val : f64,
+    pub suffix : FloatSuffix,
+}
+
+pub struct Bool {
+    pub span : Span,
+    pub val : bool,
 }
 
 pub struct Char {
-    pub lnum : u64,
+    pub span : Span,
     pub val : char,
 }
 
 pub struct Str {
-    pub lnum : u64,
+    pub span : Span,
     pub val : String,
 }
 
 pub struct Array {
-    pub lnum : u64,
+    pub span : Span,
     pub val : Vec<Node>,
 }
 
 // An expression statement, of the form EXPR ; NEXT
 pub struct Statement { 
-    pub lnum : u64,
+    pub span : Span,
     pub expr : Node,
     pub next : Node,
 }
 
 // A block statement , {...} which contains a statement
 pub struct Block { 
-    pub lnum : u64,
+    pub span : Span,
     pub statements : Node, // Statements within the block
+    #[allow(dead_code)] // Not read yet
+    pub scope : Option<Rc<RefCell<SymbolTable>>>, // Bound by name resolution
+}
+
+// An array declarator, of the form DECL[LEN]. decl is the declaration of the elements, such as
+// int a, or another array declarator for multidimensional arrays. The len is Empty if it is to be
+// inferred from the initializer
+pub struct ArrayDecl {
+    pub span : Span,
+    pub decl : Node,
+    pub len : Node,
 }
 
 pub struct Id {
-    pub lnum : u64,
+    pub span : Span,
     pub name : String,
 }
 
 pub struct InfixOp {
-    pub lnum : u64,
+    pub span : Span,
+    pub op_type : Token,
+    pub lhs : Node,
+    pub rhs : Node,
+}
+
+// A short circuiting logical operator (&& or ||). Unlike an InfixOp, the rhs is only evaluated if
+// the lhs does not already determine the result
+pub struct Logical {
+    pub span : Span,
     pub op_type : Token,
     pub lhs : Node,
     pub rhs : Node,
 }
 
 pub struct PrefixOp {
-    pub lnum : u64,
+    pub span : Span,
     pub op_type : Token,
     pub rhs : Node
 }
 
 pub struct PostfixOp {
-    pub lnum : u64,
+    pub span : Span,
     pub op_type : Token,
     pub lhs : Node,
 }
 
 pub struct Funct {
-    pub lnum : u64,
+    pub span : Span,
     pub name : Node,
     pub args : Vec<Node>
 }
 
+// A function declaration, of the form RET_TYPE NAME(PARAMS) = BODY. The body is Empty if the
+// function is only declared, and not defined
+pub struct FunctDecl {
+    pub span : Span,
+    pub name : String,
+    pub params : Vec<Param>,
+    pub ret_type : Vec<Token>, // The type specifiers of the return type, such as unsigned long, then a Star per pointer
+    pub body : Node,
+}
+
+// An anonymous function, of the form RET_TYPE (PARAMS) => BODY
+pub struct Lambda {
+    pub span : Span,
+    pub params : Vec<Param>,
+    pub ret_type : Vec<Token>,
+    pub body : Node,
+    pub captures : Vec<String>, // The variables of enclosing functions it uses. Bound by name resolution
+}
+
+// A function parameter. decl is the declaration expression of the parameter, such as int a
+pub struct Param {
+    pub span : Span,
+    pub name : String,
+    pub decl : Node,
+}
+
+// A struct definition, of the form struct NAME { FIELDS }. Each field is a declaration, such as
+// int x
+pub struct StructDecl {
+    pub span : Span,
+    pub name : String,
+    pub fields : Vec<Node>,
+}
+
+// A struct literal, of the form NAME { FIELD : VAL, ... }. Fields which are not given are zeroed
+pub struct StructLit {
+    pub span : Span,
+    pub name : String,
+    pub fields : Vec<FieldInit>,
+}
+
+pub struct FieldInit {
+    pub span : Span,
+    pub name : String,
+    pub val : Node,
+}
+
+// Access to a field of a struct. op_type is Dot for a struct, or Arrow for a pointer to one
+pub struct Member {
+    pub span : Span,
+    pub op_type : Token,
+    pub lhs : Node,
+    pub field : String,
+    pub offset : Option<u64>, // Offset of the field in bits. Calculated by the resolver
+}
+
+// The name of a type, without a variable being declared, such as unsigned long or struct P *.
+// pointers is the number of stars following the specifiers
+pub struct TypeName {
+    pub span : Span,
+    pub specs : Vec<Token>,
+    pub pointers : usize,
+}
+
+// An enum definition, of the form enum NAME { VARIANT(TYPES), ... }. Each value of the enum is
+// one of its variants, along with the values of that variant's fields
+pub struct EnumDecl {
+    pub span : Span,
+    pub name : String,
+    pub variants : Vec<Variant>,
+}
+
+pub struct Variant {
+    pub span : Span,
+    pub name : String,
+    pub fields : Vec<TypeName>,
+}
+
+// A match expression, of the form match (VAL) { PATTERN => BODY, ... }. The value of the match is
+// the body of the arm whose pattern matches the variant of val
+pub struct Match {
+    pub span : Span,
+    pub val : Node,
+    pub arms : Vec<MatchArm>,
+}
+
+// An arm of a match. variant is None for the wildcard pattern _, which matches any variant. The
+// bindings are the names given to the fields of the variant, or _ to ignore a field
+pub struct MatchArm {
+    pub span : Span,
+    pub variant : Option<String>,
+    pub bindings : Vec<String>,
+    pub body : Node,
+    pub tag : Option<usize>, // Tag of the matched variant. Calculated by the resolver
+}
+
+// An import of another module, of the form import "PATH" or import NAME. The path is relative to
+// the directory of the importing module
+pub struct Import {
+    pub span : Span,
+    pub path : String,
+    pub module : Option<PathBuf>, // The path of the loaded module. Bound by the module loader
+}
+
+// A switch expression, of the form switch (VAL) { case CONSTS: EXPR; default: EXPR }. Only the
+// arm with a case equal to the value is evaluated, with no fall through into the arms after it
+pub struct Switch {
+    pub span : Span,
+    pub val : Node,
+    pub arms : Vec<SwitchArm>,
+    pub table : Option<JumpTable>, // Built by the resolver if the cases are dense enough
+}
+
+// An arm of a switch. cases is None for the default arm, which is taken if no case matches
+pub struct SwitchArm {
+    pub span : Span,
+    pub cases : Option<Vec<Node>>,
+    pub body : Node,
+}
+
+// The arm to jump to for each value of a switch, starting from low. Values with no case are None,
+// and take the default arm
+pub struct JumpTable {
+    pub low : i64,
+    pub arms : Vec<Option<usize>>,
+}
+
+// A type alias, of the form typedef DECLARATION or type NAME = TYPE. A typedef names the type that
+// the declaration would otherwise declare a variable of
+pub struct TypeDef {
+    pub span : Span,
+    pub name : String,
+    pub aliased : Aliased,
+}
+
+pub enum Aliased {
+    Decl(Node),
+    TypeName(TypeName),
+}
+
+// A sizeof expression, of the form sizeof(TYPE) or sizeof EXPR. The operand is never evaluated,
+// only its type is needed
+pub struct Sizeof {
+    pub span : Span,
+    pub arg : SizeofArg,
+    pub size : Option<u64>, // The size of the operand in bytes. Calculated by the resolver
+}
+
+pub enum SizeofArg {
+    TypeName(TypeName),
+    Expr(Node),
+}
+
+// An explicit conversion, of the form (TYPE) EXPR
+pub struct Cast {
+    pub span : Span,
+    pub type_name : TypeName,
+    pub expr : Node,
+}
+
 pub struct If {
-    pub lnum : u64,
+    pub span : Span,
     pub cond : Node,
     pub t_expr : Node,
     pub f_expr : Node,
 }
+
+// A while loop is an expression whose value is the value of its body on the last iteration, or the
+// value it is broken out of with. If the body never runs, the loop evaluates to the zero value of
+// the body's type
+pub struct While {
+    pub span : Span,
+    pub label : Option<String>,
+    pub cond : Node,
+    pub body : Node,
+}
+
+// A C-style for loop, for(INIT; COND; STEP) BODY. Anything declared in init is scoped to the loop.
+// Like a while loop, evaluates to the value of the body on the last iteration
+pub struct For {
+    pub span : Span,
+    pub label : Option<String>,
+    pub init : Node,
+    pub cond : Node,
+    pub step : Node,
+    pub body : Node,
+}
+
+// Exit the innermost loop, or the enclosing loop with the given label, making val the value of
+// that loop
+pub struct Break {
+    pub span : Span,
+    pub label : Option<String>,
+    pub val : Node,
+}
+
+// Skip to the next iteration of the innermost loop, or the enclosing loop with the given label
+pub struct Continue {
+    pub span : Span,
+    pub label : Option<String>,
+}
@@ -11,33 +11,75 @@ pub trait AstToucher<T> {
     fn match_variant(&mut self, n : &mut Node) -> T {
         match n {
             Node::Empty => self.walk_empty(),
+            Node::Invalid(val) => self.walk_invalid(val),
             Node::Int(val) => self.walk_int(val),
+            Node::Float(val) => self.walk_float(val),
+            Node::Bool(val) => self.walk_bool(val),
             Node::Char(val) => self.walk_char(val),
             Node::Str(val) => self.walk_str(val),
             Node::Array(val) => self.walk_array(val),
+            Node::ArrayDecl(val) => self.walk_array_decl(val),
             Node::Statement(val) => self.walk_statement(val),
             Node::Block(val) => self.walk_block(val),
             Node::Id(val) => self.walk_id(val),
             Node::InfixOp(val) => self.walk_infix(val),
+            Node::Logical(val) => self.walk_logical(val),
             Node::PrefixOp(val) => self.walk_prefix(val),
             Node::PostfixOp(val) => self.walk_postfix(val),
             Node::Funct(val) => self.walk_funct(val),
+            Node::FunctDecl(val) => self.walk_funct_decl(val),
+            Node::Lambda(val) => self.walk_lambda(val),
+            Node::StructDecl(val) => self.walk_struct_decl(val),
+            Node::StructLit(val) => self.walk_struct_lit(val),
+            Node::Member(val) => self.walk_member(val),
+            Node::EnumDecl(val) => self.walk_enum_decl(val),
+            Node::Match(val) => self.walk_match(val),
+            Node::Switch(val) => self.walk_switch(val),
+            Node::Import(val) => self.walk_import(val),
+            Node::TypeDef(val) => self.walk_typedef(val),
+            Node::Sizeof(val) => self.walk_sizeof(val),
+            Node::Cast(val) => self.walk_cast(val),
             Node::If(val) => self.walk_if(val),
+            Node::While(val) => self.walk_while(val),
+            Node::For(val) => self.walk_for(val),
+            Node::Break(val) => self.walk_break(val),
+            Node::Continue(val) => self.walk_continue(val),
         }
 
     }
 
     fn walk_empty(&mut self) -> T;
+    fn walk_invalid(&mut self, inner : &mut super::Invalid) -> T;
     fn walk_int(&mut self, inner : &mut super::Int) -> T;
+    fn walk_float(&mut self, inner : &mut super::Float) -> T;
+    fn walk_bool(&mut self, inner : &mut super::Bool) -> T;
     fn walk_char(&mut self, inner : &mut super::Char) -> T;
     fn walk_str(&mut self, inner : &mut super::Str) -> T;
     fn walk_array(&mut self, inner : &mut super::Array) -> T;
+    fn walk_array_decl(&mut self, inner : &mut super::ArrayDecl) -> T;
     fn walk_statement(&mut self, inner : &mut super::Statement) -> T;
     fn walk_block(&mut self, inner : &mut super::Block) -> T;
     fn walk_id(&mut self, inner : &mut super::Id) -> T;
     fn walk_infix(&mut self, inner : &mut super::InfixOp) -> T;
+    fn walk_logical(&mut self, inner : &mut super::Logical) -> T;
     fn walk_prefix(&mut self, inner : &mut super::PrefixOp) -> T;
     fn walk_postfix(&mut self, inner : &mut super::PostfixOp) -> T;
     fn walk_funct(&mut self, inner : &mut super::Funct) -> T;
+    fn walk_funct_decl(&mut self, inner : &mut super::FunctDecl) -> T;
+    fn walk_lambda(&mut self, inner : &mut super::Lambda) -> T;
+    fn walk_struct_decl(&mut self, inner : &mut super::StructDecl) -> T;
+    fn walk_struct_lit(&mut self, inner : &mut super::StructLit) -> T;
+    fn walk_member(&mut self, inner : &mut super::Member) -> T;
+    fn walk_enum_decl(&mut self, inner : &mut super::EnumDecl) -> T;
+    fn walk_match(&mut self, inner : &mut super::Match) -> T;
+    fn walk_switch(&mut self, inner : &mut super::Switch) -> T;
+    fn walk_import(&mut self, inner : &mut super::Import) -> T;
+    fn walk_typedef(&mut self, inner : &mut super::TypeDef) -> T;
+    fn walk_sizeof(&mut self, inner : &mut super::Sizeof) -> T;
+    fn walk_cast(&mut self, inner : &mut super::Cast) -> T;
     fn walk_if(&mut self, inner : &mut super::If) -> T;
+    fn walk_while(&mut self, inner : &mut super::While) -> T;
+    fn walk_for(&mut self, inner : &mut super::For) -> T;
+    fn walk_break(&mut self, inner : &mut super::Break) -> T;
+    fn walk_continue(&mut self, inner : &mut super::Continue) -> T;
 }
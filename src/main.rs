@@ -1,11 +1,13 @@
+// The struct initializers throughout spell out `field : field`
+#![allow(clippy::redundant_field_names)]
+
 use std::io;
-use std::fs::File;
 use std::env;
 
 use ast::format::AstFormat;
 use ast::walker::AstWalker;
-use scanner::Scanner;
-use parser::Parser;
+use module::ModuleLoader;
+use name_resolution::Resolver;
 
 mod ast;
 
@@ -13,23 +15,34 @@ mod ast;
 mod scanner;
 mod parser;
 mod interp;
+mod module;
 mod name_resolution;
 
 fn main() -> io::Result<()> {
     let filepath = parse_args(env::args());
-    let file = File::open(&filepath)?;
-    let mut scanner = Scanner::new(file);
-    /*
-    for tok in scanner.collect::<Vec<_>>() {
-        println!("{tok:?}");
+    let mut loader = ModuleLoader::new();
+    loader.load_root(&filepath)?;
+    // Imported modules are resolved before the modules which import them
+    let mut resolver = Resolver::new();
+    for module in loader.modules_mut() {
+        resolver.resolve_module(module);
+    }
+    for module in loader.modules() {
+        let mut ast_formatter = AstFormat::new(); 
+        println!("{}", ast_formatter.walk(&module.ast));
+    }
+    for e in loader.lex_errors() {
+        eprintln!("error: {e}");
+    }
+    for e in loader.parse_errors() {
+        eprintln!("error: {e}");
+    }
+    for e in loader.errors() {
+        eprintln!("error: {e}");
+    }
+    for e in resolver.errors() {
+        eprintln!("error: {e}");
     }
-    let file2 = File::open(&filepath)?;
-    scanner = Scanner::new(file2);
-    */
-    let mut parser = Parser::new();
-    let ast = parser.gen_ast(&mut scanner);
-    let mut ast_formatter = AstFormat::new(); 
-    println!("{}", ast_formatter.walk(&ast));
     Ok(())
 }
 
@@ -0,0 +1,32 @@
+use std::fmt;
+
+use crate::scanner::span::Span;
+
+// The problems the module loader can find with the imports of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleErrorKind {
+    Unreadable { path : String, reason : String }, // An imported module which could not be opened
+    ImportCycle(Vec<String>), // Modules which import each other, in the order they are imported
+}
+
+// A problem with an import. The import is left unbound, and loading continues with the next one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleError {
+    pub kind : ModuleErrorKind,
+    pub span : Span,
+}
+
+impl fmt::Display for ModuleErrorKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModuleErrorKind::Unreadable { path, reason } => write!(f, "can't read module {path}: {reason}"),
+            ModuleErrorKind::ImportCycle(cycle) => write!(f, "modules import each other: {}", cycle.join(" -> ")),
+        }
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
@@ -0,0 +1,221 @@
+use std::{collections::HashMap, fs, io, path::{Path, PathBuf}};
+
+use error::{ModuleError, ModuleErrorKind};
+
+use crate::{ast::{self, Node}, parser::{Parser, error::ParseError}, scanner::{Scanner, error::LexError, span::Span, token::Token}};
+
+pub mod error;
+
+// A source file of the program, and the ast it was parsed into
+pub struct Module {
+    pub path : PathBuf, // Canonical path, which identifies the module however it is imported
+    pub ast : Node,
+    pub type_names : Vec<String>, // The type aliases it exports
+}
+
+// Load a program which is split across multiple source files. The modules a module imports are
+// loaded before it is parsed, relative to the directory of the importing module, since the type
+// aliases they export change how it is parsed
+pub struct ModuleLoader {
+    modules : Vec<Module>, // Each module comes after the modules it imports
+    // The chain of imports leading to the module currently being loaded, by canonical path and the
+    // name the module was loaded by
+    loading : Vec<(PathBuf, String)>,
+    lex_errors : Vec<LexError>,
+    parse_errors : Vec<ParseError>,
+    errors : Vec<ModuleError>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        ModuleLoader {
+            modules : Vec::new(),
+            loading : Vec::new(),
+            lex_errors : Vec::new(),
+            parse_errors : Vec::new(),
+            errors : Vec::new(),
+        }
+    }
+
+    // Return the loaded modules, in the order they should be resolved in. The root module is last
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn modules_mut(&mut self) -> &mut [Module] {
+        &mut self.modules
+    }
+
+    pub fn lex_errors(&self) -> &[LexError] {
+        &self.lex_errors
+    }
+
+    pub fn parse_errors(&self) -> &[ParseError] {
+        &self.parse_errors
+    }
+
+    // Return the errors found in the imports of the modules, in the order they were found
+    pub fn errors(&self) -> &[ModuleError] {
+        &self.errors
+    }
+
+    // Load the root module of the program, and every module it imports
+    pub fn load_root(&mut self, name : &str) -> io::Result<()> {
+        let source = fs::read_to_string(name)?;
+        let path = Path::new(name).canonicalize()?;
+        self.load(&source, name, path);
+        Ok(())
+    }
+
+    fn load(&mut self, source : &str, name : &str, path : PathBuf) {
+        let dir = Path::new(name).parent().unwrap_or(Path::new("")).to_path_buf();
+        self.loading.push((path.clone(), name.to_string()));
+        let mut imported = HashMap::new();
+        let mut type_names = Vec::new();
+        for (import, span) in scan_imports(source, name) {
+            if let Some(module) = self.load_import(&import, &dir, &span) {
+                let exported = &self.modules.iter().find(|m| m.path == module).expect("Imported module not loaded").type_names;
+                type_names.extend(exported.iter().cloned());
+                imported.insert(import, module);
+            }
+        }
+        self.loading.pop();
+        let mut scanner = Scanner::new(source.as_bytes(), name);
+        let mut parser = Parser::new();
+        parser.import_type_names(&type_names);
+        let (mut ast, parse_errors) = parser.gen_ast(&mut scanner);
+        self.lex_errors.extend(scanner.errors().iter().cloned());
+        self.parse_errors.extend(parse_errors);
+        for import in top_level_imports(&mut ast) {
+            import.module = imported.get(&import.path).cloned();
+        }
+        let type_names = parser.exported_type_names().to_vec();
+        self.modules.push(Module { path : path, ast : ast, type_names : type_names });
+    }
+
+    // Load the module an import at span names, if it hasn't been already, and return its canonical
+    // path. None if it couldn't be loaded, which has been reported
+    fn load_import(&mut self, import : &str, dir : &Path, span : &Span) -> Option<PathBuf> {
+        let mut name = dir.join(import);
+        // Modules are C source files, so the extension can be left out
+        if name.extension().is_none() {
+            name.set_extension("c");
+        }
+        let read = fs::read_to_string(&name).and_then(|source| Ok((source, name.canonicalize()?)));
+        let (source, path) = match read {
+            Ok(read) => read,
+            Err(e) => {
+                let kind = ModuleErrorKind::Unreadable { path : name.display().to_string(), reason : e.to_string() };
+                self.errors.push(ModuleError { kind : kind, span : span.clone() });
+                return None
+            },
+        };
+        // A module can't be resolved before itself, so it can't import a module which imports it
+        if let Some(i) = self.loading.iter().position(|(loading, _)| *loading == path) {
+            let mut cycle : Vec<String> = self.loading[i..].iter().map(|(_, name)| name.clone()).collect();
+            cycle.push(name.display().to_string());
+            self.errors.push(ModuleError { kind : ModuleErrorKind::ImportCycle(cycle), span : span.clone() });
+            return None
+        }
+        if !self.modules.iter().any(|module| module.path == path) {
+            self.load(&source, &name.to_string_lossy(), path.clone());
+        }
+        Some(path)
+    }
+}
+
+// Return the paths imported by a module, along with the spans of the imports. Only the tokens are
+// needed to find them, so the module doesn't need to be parsed first
+fn scan_imports(source : &str, name : &str) -> Vec<(String, Span)> {
+    let mut scanner = Scanner::new(source.as_bytes(), name);
+    let mut imports = Vec::new();
+    while let Some(tok) = scanner.next() {
+        if tok != Token::Import {
+            continue;
+        }
+        let start = scanner.span.clone();
+        if let Some(Token::StrConst(path) | Token::Id(path)) = scanner.peek() {
+            scanner.next();
+            imports.push((path, start.to(&scanner.span)));
+        }
+    }
+    imports
+}
+
+// Return the imports among the top level statements of a module. Imports anywhere else are
+// reported by name resolution
+fn top_level_imports(n : &mut Node) -> Vec<&mut ast::Import> {
+    match n {
+        Node::Statement(s) => {
+            let s = &mut **s;
+            let mut imports = top_level_imports(&mut s.expr);
+            imports.extend(top_level_imports(&mut s.next));
+            imports
+        },
+        Node::Import(import) => Vec::from([&mut **import]),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name_resolution::{Resolver, error::ResolveErrorKind};
+
+    // Write the given source files to a fresh directory named after the test, and return the
+    // directory
+    fn write_modules(test : &str, files : &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("modules-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Couldn't create module directory");
+        for (name, source) in files {
+            fs::write(dir.join(name), source).expect("Couldn't write module");
+        }
+        dir
+    }
+
+    // Load and resolve the program rooted at main.c in dir, and return the kinds of the import
+    // errors and resolution errors found. The program should be free of syntax errors
+    fn load(dir : &Path) -> (Vec<ModuleErrorKind>, Vec<ResolveErrorKind>) {
+        let mut loader = ModuleLoader::new();
+        loader.load_root(&dir.join("main.c").to_string_lossy()).expect("Couldn't read main.c");
+        assert!(loader.parse_errors().is_empty(), "{:?}", loader.parse_errors());
+        let mut resolver = Resolver::new();
+        for module in loader.modules_mut() {
+            resolver.resolve_module(module);
+        }
+        let module_errors = loader.errors().iter().map(|e| e.kind.clone()).collect();
+        (module_errors, resolver.errors().iter().map(|e| e.kind.clone()).collect())
+    }
+
+    #[test]
+    fn public_names_are_imported() {
+        let dir = write_modules("public", &[
+            ("main.c", "import util; import \"shapes.c\"; Num n = twice(2); struct P p = P { x : n }; hidden;"),
+            ("util.c", "pub typedef long Num; pub Num twice(Num a) = a * 2; int hidden = 1;"),
+            ("shapes.c", "import util; pub struct P { Num x; };"),
+        ]);
+        let (module_errors, errors) = load(&dir);
+        assert!(module_errors.is_empty(), "{module_errors:?}");
+        assert_eq!(errors, [ResolveErrorKind::UndefinedName("hidden".to_string())]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn imports_which_cant_be_loaded_are_reported() {
+        let dir = write_modules("unloadable", &[
+            ("main.c", "import missing; import a;"),
+            ("a.c", "import b;"),
+            ("b.c", "import a;"),
+        ]);
+        let (module_errors, _) = load(&dir);
+        assert!(matches!(module_errors.as_slice(), [
+            ModuleErrorKind::Unreadable { .. },
+            ModuleErrorKind::ImportCycle(cycle),
+        ] if cycle.len() == 3), "{module_errors:?}");
+        let _ = fs::remove_dir_all(&dir);
+        let dir = write_modules("nested", &[("main.c", "int f() = { import util; };"), ("util.c", "")]);
+        assert_eq!(load(&dir).1, [ResolveErrorKind::ImportNotGlobal]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::scanner::{span::Span, token::Token};
+
+use super::symbol::Types;
+
+// The problems name resolution and type checking can find in a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveErrorKind {
+    ArrayElementMismatch, // Elements of an array constant have different types
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    UndefinedLabel(String),
+    BreakValueMismatch, // Breaks out of the same loop with values of different types
+    UndefinedName(String),
+    Redeclaration(String), // A name declared twice in the same scope
+    InvalidDeclaration, // A type specifier applied to something other than a declarator
+    InvalidTypeSpec(Vec<Token>), // Type specifiers which don't name a type together, such as long char
+    NotAssignable,
+    NotAddressable, // The address of something which is not stored in memory
+    ConstAssignment { name : String, const_span : Span }, // Assignment to a variable made const at const_span
+    InvalidModifier(Token), // A modifier applied to something other than a variable
+    InvalidOperands(Token),
+    TypeMismatch { expected : Types, found : Types },
+    NotCallable(Types),
+    ArgCountMismatch { expected : usize, found : usize },
+    ReturnOutsideFunction,
+    NonConstArrayLength, // An array length which is not an integer constant
+    UnknownArrayLength, // An array with no length and no initializer to infer it from
+    ArrayInitTooLong { len : usize, init_len : usize },
+    TooLarge(String), // A variable or type whose size in bits doesn't fit in 64 bits
+    UndefinedStruct(String),
+    NoField { struct_type : Types, field : String },
+    DuplicateField(String),
+    IncompleteField(String), // A field which contains the struct being defined, rather than pointing to it
+    UndefinedEnum(String),
+    IncompleteVariant(String), // A variant with a field which contains the enum being defined
+    NotAnEnum(Types), // A match on a value which is not an enum
+    NoVariant { enum_type : Types, variant : String },
+    PatternArity { variant : String, expected : usize, found : usize }, // A pattern binding the wrong number of fields
+    UnreachableArm, // A match arm for a variant which has already been matched
+    NonExhaustiveMatch(Vec<String>), // A match which doesn't cover these variants
+    ImportNotGlobal, // An import which is not at the top level of a module
+    PubNotGlobal, // pub applied to a declaration which is not at the top level of a module
+    IncompleteSizeof(Types), // The size of a struct or enum taken inside its own definition
+    InvalidCast { from : Types, to : Types },
+    NonIntegerSwitch(Types), // A switch on a value which is not an integer
+    NonConstCase, // A case which is not an integer constant
+    DuplicateCase(i64), // A case for a value which already has one in the same switch
+    DuplicateDefault,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveError {
+    pub kind : ResolveErrorKind,
+    pub span : Span,
+}
+
+impl fmt::Display for ResolveErrorKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveErrorKind::ArrayElementMismatch => write!(f, "array has elements of different type"),
+            ResolveErrorKind::BreakOutsideLoop => write!(f, "break outside of a loop"),
+            ResolveErrorKind::ContinueOutsideLoop => write!(f, "continue outside of a loop"),
+            ResolveErrorKind::UndefinedLabel(l) => write!(f, "no enclosing loop is labelled {l}"),
+            ResolveErrorKind::BreakValueMismatch => write!(f, "loop is broken out of with values of different type"),
+            ResolveErrorKind::UndefinedName(name) => write!(f, "{name} is not defined in this scope"),
+            ResolveErrorKind::Redeclaration(name) => write!(f, "{name} is already declared in this scope"),
+            ResolveErrorKind::InvalidTypeSpec(specs) => write!(f, "{specs:?} is not a valid combination of type specifiers"),
+            ResolveErrorKind::InvalidDeclaration => write!(f, "type specifier must be applied to a name"),
+            ResolveErrorKind::NotAssignable => write!(f, "left side of assignment can't be assigned to"),
+            ResolveErrorKind::ConstAssignment { name, const_span } => write!(f, "can't assign to {name}, which was made const at {const_span}"),
+            ResolveErrorKind::InvalidModifier(m) => write!(f, "{m:?} must be applied to a variable"),
+            ResolveErrorKind::NotAddressable => write!(f, "can't take the address of a value which is not a variable"),
+            ResolveErrorKind::InvalidOperands(op) => write!(f, "invalid operand types for {op:?}"),
+            ResolveErrorKind::TypeMismatch { expected, found } => write!(f, "expected {expected}, found {found}"),
+            ResolveErrorKind::NotCallable(t) => write!(f, "{t} is not a function"),
+            ResolveErrorKind::ArgCountMismatch { expected, found } => write!(f, "function takes {expected} arguments, but {found} were given"),
+            ResolveErrorKind::ReturnOutsideFunction => write!(f, "return outside of a function"),
+            ResolveErrorKind::NonConstArrayLength => write!(f, "array length must be an integer constant"),
+            ResolveErrorKind::UnknownArrayLength => write!(f, "array has no length, and no initializer to infer it from"),
+            ResolveErrorKind::ArrayInitTooLong { len, init_len } => write!(f, "initializer has {init_len} elements, but the array only has {len}"),
+            ResolveErrorKind::TooLarge(name) => write!(f, "{name} is too large"),
+            ResolveErrorKind::UndefinedStruct(name) => write!(f, "struct {name} is not defined in this scope"),
+            ResolveErrorKind::NoField { struct_type, field } => write!(f, "{struct_type} has no field {field}"),
+            ResolveErrorKind::DuplicateField(name) => write!(f, "field {name} is already declared in this struct"),
+            ResolveErrorKind::IncompleteField(name) => write!(f, "field {name} has a struct type which is not defined yet"),
+            ResolveErrorKind::UndefinedEnum(name) => write!(f, "enum {name} is not defined in this scope"),
+            ResolveErrorKind::IncompleteVariant(name) => write!(f, "variant {name} has a field whose type is not defined yet"),
+            ResolveErrorKind::NotAnEnum(t) => write!(f, "can only match on an enum, but found {t}"),
+            ResolveErrorKind::NoVariant { enum_type, variant } => write!(f, "{enum_type} has no variant {variant}"),
+            ResolveErrorKind::PatternArity { variant, expected, found } => write!(f, "{variant} has {expected} fields, but the pattern binds {found}"),
+            ResolveErrorKind::UnreachableArm => write!(f, "unreachable match arm, since its variant is already matched"),
+            ResolveErrorKind::ImportNotGlobal => write!(f, "imports must be at the top level of a module"),
+            ResolveErrorKind::PubNotGlobal => write!(f, "only declarations at the top level of a module can be pub"),
+            ResolveErrorKind::IncompleteSizeof(t) => write!(f, "can't take the size of {t}, which is not defined yet"),
+            ResolveErrorKind::InvalidCast { from, to } => write!(f, "can't cast {from} to {to}"),
+            ResolveErrorKind::NonIntegerSwitch(t) => write!(f, "can't switch on {t}, which is not an integer"),
+            ResolveErrorKind::NonConstCase => write!(f, "case must be an integer constant"),
+            ResolveErrorKind::DuplicateCase(v) => write!(f, "case {v} is already handled by an earlier arm"),
+            ResolveErrorKind::DuplicateDefault => write!(f, "switch already has a default arm"),
+            ResolveErrorKind::NonExhaustiveMatch(missing) => write!(f, "match doesn't cover the variants {}", missing.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
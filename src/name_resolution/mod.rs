@@ -1,101 +1,1696 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{collections::HashMap, path::PathBuf, rc::Rc};
 
-use symbol::{Symbol, SymbolTable, Types, };
+use error::{ResolveError, ResolveErrorKind};
+use symbol::{EnumLayout, Modifiers, Scope, StructLayout, Symbol, SymbolTable, Types, };
 
-use crate::ast::{self, Node, toucher::AstToucher};
+use crate::{ast::{self, Node, toucher::AstToucher}, module::Module, scanner::{span::Span, token::{FloatSuffix, IntSuffix, Token}}};
 
+pub mod error;
 pub mod symbol;
 
+// A loop enclosing the node currently being resolved
+struct LoopCtx {
+    label : Option<String>,
+    break_type : Option<Types>, // Type of the values the loop is broken out of with, if any
+}
+
 // Perform name resolution and type checking on the ast, and create a tree of scopes that is bound
 // to the ast.
 pub struct Resolver {
     // Each different stack frame gets its own symbol table
     frame_tables : Vec<SymbolTable>,
+    loops : Vec<LoopCtx>,
+    fn_rets : Vec<Types>, // Return types of the functions enclosing the node currently being resolved
+    // Stack frames of the functions enclosing the node currently being resolved, along with the
+    // variables of outer functions each captures
+    captures : Vec<(usize, Vec<String>)>,
+    exports : Scope, // The pub names of the module currently being resolved
+    module_exports : HashMap<PathBuf, Scope>, // The pub names of each module resolved so far
+    errors : Vec<ResolveError>,
 }
 
 impl Resolver {
     pub fn new() -> Self {
         Resolver {
-            frame_tables : Vec::from([SymbolTable::new(0)]),
+            frame_tables : Vec::from([SymbolTable::new()]),
+            loops : Vec::new(),
+            fn_rets : Vec::new(),
+            captures : Vec::new(),
+            exports : Scope::new(0),
+            module_exports : HashMap::new(),
+            errors : Vec::new(),
+        }
+    }
+
+    // Return the errors found so far, in the order they were found
+    pub fn errors(&self) -> &[ResolveError] {
+        &self.errors
+    }
+
+    // Resolve a module in its own global scope. The modules it imports should have already been
+    // resolved, so that the names they export are known
+    pub fn resolve_module(&mut self, module : &mut Module) {
+        self.frame_tables.last_mut().expect("No stack frame").push_module();
+        self.walk(&mut module.ast);
+        self.frame_tables.last_mut().expect("No stack frame").pop_module();
+        let exports = std::mem::replace(&mut self.exports, Scope::new(0));
+        self.module_exports.insert(module.path.clone(), exports);
+    }
+
+    fn error(&mut self, kind : ResolveErrorKind, span : &Span) {
+        self.errors.push(ResolveError { kind : kind, span : span.clone() });
+    }
+
+    // Resolve the body of a loop with the given label. The value of the loop is the value of its
+    // body, unless it is broken out of with a value
+    fn walk_loop_body(&mut self, label : &Option<String>, body : &mut Node) -> Types {
+        self.loops.push(LoopCtx { label : label.clone(), break_type : None });
+        let body_type = self.walk(body);
+        let ctx = self.loops.pop().expect("Unbalanced loop stack");
+        ctx.break_type.unwrap_or(body_type).with_assignable(false)
+    }
+
+    // Declare a variable in the current scope, and return a reference to it
+    fn declare(&mut self, name : &str, val_type : &Types, span : &Span) -> Types {
+        if self.frame_tables.last().expect("No stack frame").is_local(name) {
+            self.error(ResolveErrorKind::Redeclaration(name.to_string()), span);
         }
+        self.frame_tables.last_mut().expect("No stack frame").insert(name, val_type, Modifiers::default());
+        val_type.with_assignable(true)
+    }
+
+    // Declare the array declared by an array declarator
+    fn declare_array(&mut self, inner : &ast::ArrayDecl, init : Option<(&Types, &Span)>) -> Types {
+        match self.array_type(inner, init) {
+            Some((val_type, name, decl_span)) => self.declare(name, &val_type, decl_span),
+            None => Types::Undefined(false),
+        }
+    }
+
+    // Return the type and name of the array declared by an array declarator, along with the span
+    // of its declaration. If there is an initializer, the length of the array may be inferred from
+    // it, and can't be shorter than it
+    fn array_type<'a>(&mut self, inner : &'a ast::ArrayDecl, init : Option<(&Types, &Span)>) -> Option<(Types, &'a str, &'a Span)> {
+        // The outermost declarator gives the length of the innermost dimension, so collect the
+        // lengths working inwards until the declaration of the elements is reached
+        let mut lens = Vec::new();
+        let mut curr = inner;
+        loop {
+            lens.push(self.array_len(&curr.len));
+            match &curr.decl {
+                Node::ArrayDecl(d) => curr = d,
+                _ => break,
+            }
+        }
+        let (base, name, decl_span) = match &curr.decl {
+            Node::PrefixOp(p) => match self.declared_base(p).and_then(|(base, declarator)| declarator_type(base, declarator)) {
+                Some((base, name)) => (base, name, &p.span),
+                None => {
+                    self.error(ResolveErrorKind::InvalidDeclaration, &p.span);
+                    return None
+                },
+            },
+            _ => {
+                self.error(ResolveErrorKind::InvalidDeclaration, &inner.span);
+                return None
+            },
+        };
+        let first_len = lens.pop().expect("Array declarator without a length");
+        let mut elem_type = base;
+        for len in lens {
+            let len = len.unwrap_or_else(|| {
+                self.error(ResolveErrorKind::UnknownArrayLength, &inner.span);
+                0
+            });
+            elem_type = self.array_of(elem_type, len, name, &inner.span);
+        }
+        let len = match (first_len, init) {
+            (Some(len), Some((Types::Array(_, _, init_len), init_span))) if *init_len > len => {
+                self.error(ResolveErrorKind::ArrayInitTooLong { len : len, init_len : *init_len }, init_span);
+                len
+            },
+            (Some(len), _) => len,
+            (None, Some((Types::Array(_, _, init_len), _))) => *init_len,
+            (None, _) => {
+                self.error(ResolveErrorKind::UnknownArrayLength, &inner.span);
+                0
+            },
+        };
+        Some((self.array_of(elem_type, len, name, &inner.span), name, decl_span))
+    }
+
+    // Return the type of an array of len elements, declared as name. Arrays too large for their
+    // size in bits to be represented are reported, and given a length of 0
+    fn array_of(&mut self, elem_type : Types, len : usize, name : &str, span : &Span) -> Types {
+        if elem_type.size().checked_mul(len as u64).is_none() {
+            self.error(ResolveErrorKind::TooLarge(name.to_string()), span);
+            return Types::Array(false, Box::new(elem_type), 0)
+        }
+        Types::Array(false, Box::new(elem_type), len)
+    }
+
+    // Return the length given in an array declarator, or None if it is to be inferred. Lengths
+    // which don't fit in a usize are certainly too large, which is reported by array_of
+    fn array_len(&mut self, len : &Node) -> Option<usize> {
+        match len {
+            Node::Empty => None,
+            Node::Int(i) => Some(usize::try_from(i.val).unwrap_or(usize::MAX)),
+            _ => {
+                self.error(ResolveErrorKind::NonConstArrayLength, len.span().expect("Array length without a span"));
+                Some(0)
+            },
+        }
+    }
+
+    // Return the type named by a sequence of type specifiers, such as unsigned long. A struct or
+    // enum type can't be combined with other specifiers
+    fn specs_type(&mut self, specs : &[Token], span : &Span) -> Types {
+        let table = self.frame_tables.last().expect("No stack frame");
+        match specs {
+            [Token::StructType(name)] => return match table.get_struct(name) {
+                Some(layout) => Types::Struct(false, layout),
+                None => {
+                    self.error(ResolveErrorKind::UndefinedStruct(name.clone()), span);
+                    Types::Undefined(false)
+                },
+            },
+            [Token::EnumType(name)] => return match table.get_enum(name) {
+                Some(layout) => Types::Enum(false, layout),
+                None => {
+                    self.error(ResolveErrorKind::UndefinedEnum(name.clone()), span);
+                    Types::Undefined(false)
+                },
+            },
+            [Token::TypeName(name)] => return match table.get_type(name) {
+                Some(aliased) => aliased,
+                None => {
+                    self.error(ResolveErrorKind::UndefinedName(name.clone()), span);
+                    Types::Undefined(false)
+                },
+            },
+            [Token::FunctType(ret_type, params)] => return self.funct_type(ret_type, params, span),
+            _ => (),
+        }
+        spec_type(specs).unwrap_or_else(|| {
+            self.error(ResolveErrorKind::InvalidTypeSpec(specs.to_vec()), span);
+            Types::Undefined(false)
+        })
+    }
+
+    // Return the type of a function with the given return type and parameter types, as in
+    // int(int *). Each parameter type ends in a Star for each pointer
+    fn funct_type(&mut self, ret_type : &[Token], params : &[Vec<Token>], span : &Span) -> Types {
+        let mut sig = Vec::from([self.specs_type(ret_type, span)]);
+        for param in params {
+            sig.push(self.pointed_type(param, span));
+        }
+        // Invalid types have already been reported
+        if sig.iter().any(|t| matches!(t, Types::Undefined(_))) {
+            return Types::Undefined(false)
+        }
+        Types::Funct(false, sig)
+    }
+
+    // Return the type named by type specifiers followed by a Star for each pointer, as in int *
+    fn pointed_type(&mut self, specs : &[Token], span : &Span) -> Types {
+        let pointers = specs.iter().rev().take_while(|t| **t == Token::Star).count();
+        let mut named = self.specs_type(&specs[..specs.len() - pointers], span);
+        for _ in 0..pointers {
+            named = Types::pointer_to(named);
+        }
+        named
+    }
+
+    // Return the type named by a type name, such as struct P *
+    fn type_name_type(&mut self, type_name : &ast::TypeName) -> Types {
+        let mut named = self.specs_type(&type_name.specs, &type_name.span);
+        if let Types::Undefined(_) = named {
+            return named
+        }
+        for _ in 0..type_name.pointers {
+            named = Types::pointer_to(named);
+        }
+        named
+    }
+
+    // Return the base type of a declaration beginning with type specifiers, along with the
+    // declarator following them. None if the specifiers are invalid, which has been reported
+    fn declared_base<'a>(&mut self, decl : &'a ast::PrefixOp) -> Option<(Types, &'a Node)> {
+        let (specs, declarator) = decl_specs(decl);
+        match self.specs_type(&specs, &decl.span) {
+            Types::Undefined(_) => None,
+            base => Some((base, declarator)),
+        }
+    }
+
+    // Return the type of the variable declared by a function parameter. int a[] declares a
+    // pointer to int
+    fn param_type(&mut self, decl : &Node) -> Types {
+        match decl {
+            Node::PrefixOp(op) if op.op_type.is_modifier() => self.param_type(&op.rhs),
+            Node::PrefixOp(op) => self.declared_base(op)
+                .and_then(|(base, declarator)| declarator_type(base, declarator))
+                .map_or(Types::Undefined(false), |(t, _)| t),
+            Node::ArrayDecl(decl) => Types::pointer_to(self.param_type(&decl.decl)),
+            _ => Types::Undefined(false),
+        }
+    }
+
+    // Return the type and name of a struct field declaration, without declaring it as a variable.
+    // None if the declaration is invalid, which has been reported
+    fn field_type<'a>(&mut self, decl : &'a Node) -> Option<(Types, &'a str)> {
+        match decl {
+            Node::PrefixOp(op) if op.op_type.is_modifier() => self.field_type(&op.rhs),
+            Node::PrefixOp(op) => {
+                let (base, declarator) = self.declared_base(op)?;
+                let field = declarator_type(base, declarator);
+                if field.is_none() {
+                    self.error(ResolveErrorKind::InvalidDeclaration, &op.span);
+                }
+                field
+            },
+            Node::ArrayDecl(decl) => self.array_type(decl, None).map(|(val_type, name, _)| (val_type, name)),
+            _ => {
+                self.error(ResolveErrorKind::InvalidDeclaration, decl.span().expect("Field without a span"));
+                None
+            },
+        }
+    }
+
+    // Export the type defined by decl from the current module. The variants of an enum are exported
+    // along with it
+    fn export_type(&mut self, decl : &Node) {
+        let table = self.frame_tables.last().expect("No stack frame");
+        match decl {
+            Node::StructDecl(s) => if let Some(layout) = table.get_struct(&s.name) {
+                self.exports.structs.insert(s.name.clone(), layout);
+            },
+            Node::EnumDecl(e) => if let Some(layout) = table.get_enum(&e.name) {
+                self.exports.enums.insert(e.name.clone(), layout);
+                for variant in &e.variants {
+                    if let Some(symbol) = table.get_symbol(&variant.name) {
+                        self.exports.map.insert(variant.name.clone(), symbol);
+                    }
+                }
+            },
+            Node::TypeDef(t) => if let Some(aliased) = table.get_type(&t.name) {
+                self.exports.types.insert(t.name.clone(), aliased);
+            },
+            _ => (),
+        }
+    }
+
+    // Return the signature of a function, which is its return type followed by its parameter types
+    fn funct_sig(&mut self, params : &[ast::Param], ret_type : &[Token], span : &Span) -> Vec<Types> {
+        let param_types : Vec<Types> = params.iter().map(|p| self.param_type(&p.decl)).collect();
+        let mut sig = Vec::from([self.pointed_type(ret_type, span)]);
+        sig.extend(param_types);
+        sig
+    }
+
+    // Resolve the parameters and body of a function with the given signature in a new stack frame,
+    // and return the variables of enclosing functions which it captures
+    fn walk_funct_body(&mut self, params : &[ast::Param], sig : &[Types], body : &mut Node, span : &Span) -> Vec<String> {
+        let ret_type = sig[0].clone();
+        let table = self.frame_tables.last_mut().expect("No stack frame");
+        let frame = table.push_frame();
+        table.push_scope();
+        for (param, param_type) in params.iter().zip(&sig[1..]) {
+            let table = self.frame_tables.last_mut().expect("No stack frame");
+            if table.is_local(&param.name) {
+                self.error(ResolveErrorKind::Redeclaration(param.name.clone()), &param.span);
+                continue;
+            }
+            table.insert(&param.name, &param_type.with_assignable(true), decl_modifiers(&param.decl));
+        }
+        // Loops outside of the function can't be broken out of from inside it
+        let outer_loops = std::mem::take(&mut self.loops);
+        self.fn_rets.push(ret_type.clone());
+        self.captures.push((frame, Vec::new()));
+        let body_type = self.walk(body);
+        let (_, captures) = self.captures.pop().expect("Unbalanced function stack");
+        self.fn_rets.pop();
+        self.loops = outer_loops;
+        // The value of the body is returned, unless it is only a declaration
+        if !matches!(body, Node::Empty) && !ret_type.compatible(&body_type) {
+            let span = body.span().unwrap_or(span).clone();
+            self.error(ResolveErrorKind::TypeMismatch { expected : ret_type, found : body_type }, &span);
+        }
+        self.frame_tables.last_mut().expect("No stack frame").pop_frame();
+        captures
+    }
+
+    // Mark a variable from the stack frame of an enclosing function as captured by every function
+    // between that one and the current one, since they all need it to be kept alive
+    fn capture(&mut self, name : &str, symbol : &Symbol) {
+        symbol.is_captured.set(true);
+        for (_, captures) in self.captures.iter_mut().rev().take_while(|(frame, _)| *frame > symbol.frame_id) {
+            if !captures.iter().any(|c| c == name) {
+                captures.push(name.to_string());
+            }
+        }
+    }
+
+    // Check the operand of an increment or decrement operator, and return the type of the result
+    fn walk_step(&mut self, op : &Token, operand : &mut Node, span : &Span) -> Types {
+        let operand_type = self.walk(operand);
+        if !operand_type.is_arithmetic() && !matches!(operand_type, Types::Pointer(..)) {
+            self.error(ResolveErrorKind::InvalidOperands(op.clone()), span);
+        } else {
+            self.check_assignable(operand, &operand_type, span);
+        }
+        operand_type.with_assignable(false)
+    }
+
+    // Check that the target of an assignment at span can be assigned to, and return whether it can
+    fn check_assignable(&mut self, target : &Node, target_type : &Types, span : &Span) -> bool {
+        if target_type.assignable() || *target_type == Types::Undefined(false) {
+            return true
+        }
+        // Point out where a const variable was made const, including when the target is a part of
+        // it or is reached through it
+        let const_span = location_name(target).and_then(|name| {
+            self.frame_tables.last().expect("No stack frame")
+                .get_symbol(name)
+                .and_then(|symbol| symbol.modifiers.borrow().is_const.clone())
+                .map(|const_span| (name.to_string(), const_span))
+        });
+        match const_span {
+            Some((name, const_span)) => self.error(ResolveErrorKind::ConstAssignment { name : name, const_span : const_span }, span),
+            None => self.error(ResolveErrorKind::NotAssignable, target.span().unwrap_or(span)),
+        }
+        false
+    }
+
+    // Resolve the target of an assignment, which may be a declaration initialized with a value of
+    // type init
+    fn walk_target(&mut self, target : &mut Node, init : Option<(&Types, &Span)>) -> Types {
+        match target {
+            Node::ArrayDecl(decl) => self.declare_array(decl, init),
+            Node::PrefixOp(op) if op.op_type.is_modifier() => {
+                self.walk_modifier(&op.op_type.clone(), &mut op.rhs, &op.span, init)
+            },
+            _ => self.walk(target),
+        }
+    }
+
+    // Apply a modifier to the variable named or declared by target, and return a reference to it
+    fn walk_modifier(&mut self, modifier : &Token, target : &mut Node, span : &Span, init : Option<(&Types, &Span)>) -> Types {
+        let target_type = self.walk_target(target, init);
+        let global = self.frame_tables.last().expect("No stack frame").is_global();
+        if *modifier == Token::Pub && !global {
+            self.error(ResolveErrorKind::PubNotGlobal, span);
+        }
+        // Types can only be exported
+        if let Node::StructDecl(_) | Node::EnumDecl(_) | Node::TypeDef(_) = target {
+            match modifier {
+                Token::Pub if global => self.export_type(target),
+                Token::Pub => (),
+                _ => self.error(ResolveErrorKind::InvalidModifier(modifier.clone()), span),
+            }
+            return target_type
+        }
+        let Some(name) = modified_name(target) else {
+            self.error(ResolveErrorKind::InvalidModifier(modifier.clone()), span);
+            return Types::Undefined(false)
+        };
+        let Some(symbol) = self.frame_tables.last().expect("No stack frame").get_symbol(name) else {
+            // Undefined names have already been reported
+            return Types::Undefined(false)
+        };
+        let mut modifiers = symbol.modifiers.borrow_mut();
+        match modifier {
+            // A variable stays const from the first time it is made const
+            Token::Const => if modifiers.is_const.is_none() {
+                modifiers.is_const = Some(span.clone());
+            },
+            Token::Static => modifiers.is_static = true,
+            Token::Pub => if global {
+                modifiers.is_pub = true;
+                self.exports.map.insert(name.to_string(), symbol.clone());
+            },
+            _ => modifiers.is_volatile = true,
+        }
+        target_type.with_assignable(modifiers.is_const.is_none())
     }
 }
 
 // Return the type information of the subast rooted at the node, if the subtree is valid
 impl AstToucher<Types> for Resolver {
     fn walk_empty(&mut self) -> Types {
-        Types::Int
+        Types::Int(false)
+    }
+
+    // Syntax errors have already been reported by the parser
+    fn walk_invalid(&mut self, _inner : &mut ast::Invalid) -> Types {
+        Types::Undefined(false)
     }
 
+    // Unsuffixed constants too large for an int are longs. Those not written in decimal are
+    // unsigned if that is enough for them to fit
     fn walk_int(&mut self, inner : &mut ast::Int) -> Types {
-        Types::Int
+        match inner.suffix {
+            IntSuffix::None if inner.val <= i32::MAX as u64 => Types::Int(false),
+            IntSuffix::None if inner.radix != 10 && inner.val <= u32::MAX as u64 => Types::UInt(false),
+            IntSuffix::None if inner.val <= i64::MAX as u64 => Types::Long(false),
+            IntSuffix::None => Types::ULong(false),
+            IntSuffix::U => Types::UInt(false),
+            IntSuffix::L => Types::Long(false),
+            IntSuffix::UL => Types::ULong(false),
+        }
+    }
+
+    fn walk_float(&mut self, inner : &mut ast::Float) -> Types {
+        match inner.suffix {
+            FloatSuffix::None => Types::Double(false),
+            FloatSuffix::F => Types::Float(false),
+        }
+    }
+
+    fn walk_bool(&mut self, _inner : &mut ast::Bool) -> Types {
+        Types::Bool(false)
     }
 
-    fn walk_char(&mut self, inner : &mut ast::Char) -> Types {
-        Types::Char
+    fn walk_char(&mut self, _inner : &mut ast::Char) -> Types {
+        Types::Char(false)
     }
 
+    // A string constant is an array of its characters, followed by a null character
     fn walk_str(&mut self, inner : &mut ast::Str) -> Types {
-        Types::Pointer(Box::new(Types::Char))
+        Types::Array(false, Box::new(Types::Char(false)), inner.val.chars().count() + 1)
     }
 
     fn walk_array(&mut self, inner : &mut ast::Array) -> Types {
-        let array_line = inner.lnum;
-        let inner_type = inner.val.iter_mut().fold(
-            None, 
-            |acc, element| {
-                let mut ret_val = acc.clone();
-                let Some(s_table) = self.frame_tables.last() else {
-                    panic!("No stack frame")
-                };
-                s_table.push_scope();
-                let Some(curr_type) = acc else {
-                    ret_val = Some(self.walk(element));
-                    s_table.pop_scope();
-                    return ret_val
-                };
-                let ele_type = self.walk(element);
-                if curr_type != ele_type {
-                    panic!("Array on line {:?} has elements of different type\n", array_line)
-                }
-                s_table.pop_scope();
-                ret_val
-            });
+        let array_span = inner.span.clone();
+        let mut inner_type = None;
+        for element in inner.val.iter_mut() {
+            // Each element is evaluated in its own scope
+            self.frame_tables.last_mut().expect("No stack frame").push_scope();
+            let ele_type = self.walk(element);
+            self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+            match &inner_type {
+                None => inner_type = Some(ele_type),
+                Some(curr_type) => if *curr_type != ele_type {
+                    self.error(ResolveErrorKind::ArrayElementMismatch, &array_span);
+                },
+            }
+        }
+        Types::Array(false, Box::new(inner_type.unwrap_or(Types::Undefined(false))), inner.val.len())
+    }
+
+    fn walk_array_decl(&mut self, inner : &mut ast::ArrayDecl) -> Types {
+        self.declare_array(inner, None)
     }
 
+    // Statements ending in a return never reach their end, so take the type of the return, which
+    // is compatible with anything
     fn walk_statement(&mut self, inner : &mut ast::Statement) -> Types {
-        self.walk(&mut inner.expr);
-        self.walk(&mut inner.next)
+        let expr_type = self.walk(&mut inner.expr);
+        let next_type = self.walk(&mut inner.next);
+        match (&inner.expr, &inner.next) {
+            (Node::PrefixOp(op), Node::Empty) if op.op_type == Token::Ret => expr_type,
+            _ => next_type,
+        }
     }
 
     fn walk_block(&mut self, inner : &mut ast::Block) -> Types {
         self.frame_tables.last_mut().expect("Left global frame").push_scope();
-        self.walk(&mut inner.statements)
+        let block_type = self.walk(&mut inner.statements);
+        self.frame_tables.last_mut().expect("Left global frame").pop_scope();
+        block_type.with_assignable(false)
     }
 
+    // Variables can be assigned to, unless they are constant
+    // Variables local to another function are captured. Global variables never need to be
     fn walk_id(&mut self, inner : &mut ast::Id) -> Types {
-
-
+        let table = self.frame_tables.last().expect("No stack frame");
+        let frame = table.frame();
+        match table.get_symbol(&inner.name) {
+            Some(symbol) => {
+                if symbol.frame_id != 0 && symbol.frame_id != frame {
+                    self.capture(&inner.name, &symbol);
+                }
+                symbol.val_type.with_assignable(symbol.modifiers.borrow().is_const.is_none())
+            },
+            None => {
+                self.error(ResolveErrorKind::UndefinedName(inner.name.clone()), &inner.span);
+                Types::Undefined(false)
+            },
+        }
     }
 
     fn walk_infix(&mut self, inner : &mut ast::InfixOp) -> Types {
-        todo!()
+        if is_assignment(&inner.op_type) {
+            // The right side is evaluated first, so that an array declaration can infer its length
+            // from its initializer
+            let rhs_type = self.walk(&mut inner.rhs);
+            let rhs_span = inner.rhs.span().unwrap_or(&inner.span).clone();
+            let lhs_type = match inner.op_type {
+                Token::Assign => self.walk_target(&mut inner.lhs, Some((&rhs_type, &rhs_span))),
+                _ => self.walk(&mut inner.lhs),
+            };
+            // Initializing a declaration is allowed, even if it declares a const variable
+            let is_init = inner.op_type == Token::Assign && is_declaration(&inner.lhs);
+            if is_init || self.check_assignable(&inner.lhs, &lhs_type, &inner.span) {
+                if inner.op_type == Token::Assign && !lhs_type.compatible(&rhs_type) {
+                    self.error(ResolveErrorKind::TypeMismatch { expected : lhs_type.clone(), found : rhs_type }, &inner.span);
+                } else if inner.op_type != Token::Assign && binary_type(&inner.op_type, &lhs_type, &rhs_type).is_none() {
+                    self.error(ResolveErrorKind::InvalidOperands(inner.op_type.clone()), &inner.span);
+                }
+            }
+            return lhs_type.with_assignable(false)
+        }
+        let lhs_type = self.walk(&mut inner.lhs);
+        let rhs_type = self.walk(&mut inner.rhs);
+        // The elements of an array can be assigned to, unless the array or what it points to is const
+        if inner.op_type == Token::LBrack {
+            return match (lhs_type.element(), lhs_type) {
+                (Some(t), _) if rhs_type.is_integer() => t,
+                (_, Types::Undefined(_)) if rhs_type.is_integer() => Types::Undefined(false),
+                _ => {
+                    self.error(ResolveErrorKind::InvalidOperands(inner.op_type.clone()), &inner.span);
+                    Types::Undefined(false)
+                },
+            }
+        }
+        binary_type(&inner.op_type, &lhs_type, &rhs_type).unwrap_or_else(|| {
+            self.error(ResolveErrorKind::InvalidOperands(inner.op_type.clone()), &inner.span);
+            Types::Undefined(false)
+        })
+    }
+
+    fn walk_logical(&mut self, inner : &mut ast::Logical) -> Types {
+        self.walk(&mut inner.lhs);
+        // The rhs is conditionally evaluated, so anything it declares can't be visible afterwards
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        self.walk(&mut inner.rhs);
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        Types::Int(false)
     }
 
     fn walk_prefix(&mut self, inner : &mut ast::PrefixOp) -> Types {
-        todo!()
+        match inner.op_type {
+            // Type specifiers declare a new variable in the current scope
+            ref spec if spec.is_type_spec() => {
+                let Some((base, declarator)) = self.declared_base(inner) else {
+                    return Types::Undefined(false)
+                };
+                match declarator_type(base, declarator) {
+                    Some((val_type, name)) => self.declare(name, &val_type, &inner.span),
+                    None => {
+                        self.error(ResolveErrorKind::InvalidDeclaration, &inner.span);
+                        Types::Undefined(false)
+                    },
+                }
+            },
+            // A return never evaluates to a value itself, since control leaves the function
+            Token::Ret => {
+                let val_type = match inner.rhs {
+                    Node::Empty => None,
+                    _ => Some(self.walk(&mut inner.rhs)),
+                };
+                match (self.fn_rets.last(), val_type) {
+                    (None, _) => self.error(ResolveErrorKind::ReturnOutsideFunction, &inner.span),
+                    (Some(ret_type), Some(val_type)) if !ret_type.compatible(&val_type) => {
+                        let expected = ret_type.clone();
+                        self.error(ResolveErrorKind::TypeMismatch { expected : expected, found : val_type }, &inner.span);
+                    },
+                    _ => (),
+                }
+                Types::Undefined(false)
+            },
+            Token::Inc | Token::Dec => self.walk_step(&inner.op_type.clone(), &mut inner.rhs, &inner.span),
+            ref modifier if modifier.is_modifier() => self.walk_modifier(&inner.op_type.clone(), &mut inner.rhs, &inner.span, None),
+            // Dereferencing a pointer gives the location it points to, which can be assigned to
+            // unless it is const
+            Token::Star => match self.walk(&mut inner.rhs) {
+                Types::Undefined(_) => Types::Undefined(false),
+                t => t.element().unwrap_or_else(|| {
+                    self.error(ResolveErrorKind::InvalidOperands(Token::Star), &inner.span);
+                    Types::Undefined(false)
+                }),
+            },
+            // Only locations have an address. The address of a const points to a const
+            Token::Amp => match self.walk(&mut inner.rhs) {
+                Types::Undefined(_) => Types::Undefined(false),
+                t if is_location(&inner.rhs) => Types::Pointer(false, Box::new(t)),
+                _ => {
+                    self.error(ResolveErrorKind::NotAddressable, &inner.span);
+                    Types::Undefined(false)
+                },
+            },
+            // Pointers are true if they are not null
+            Token::Not => {
+                let rhs_type = self.walk(&mut inner.rhs);
+                if !rhs_type.is_arithmetic() && !matches!(rhs_type, Types::Pointer(..)) {
+                    self.error(ResolveErrorKind::InvalidOperands(Token::Not), &inner.span);
+                }
+                Types::Int(false)
+            },
+            // Operands smaller than an int are promoted to int. Only integers can be complemented
+            _ => {
+                let rhs_type = self.walk(&mut inner.rhs);
+                let valid = match inner.op_type {
+                    Token::Tilde => rhs_type.is_integer(),
+                    _ => rhs_type.is_arithmetic(),
+                };
+                if !valid {
+                    self.error(ResolveErrorKind::InvalidOperands(inner.op_type.clone()), &inner.span);
+                    return Types::Undefined(false)
+                }
+                rhs_type.promote()
+            },
+        }
     }
 
     fn walk_postfix(&mut self, inner : &mut ast::PostfixOp) -> Types {
-        todo!()
+        self.walk_step(&inner.op_type.clone(), &mut inner.lhs, &inner.span)
     }
 
     fn walk_funct(&mut self, inner : &mut ast::Funct) -> Types {
-        todo!()
+        let funct_type = self.walk(&mut inner.name);
+        // f() has no arguments, rather than a single empty one
+        let mut arg_types = Vec::new();
+        for arg in inner.args.iter_mut().filter(|arg| !matches!(arg, Node::Empty)) {
+            let arg_span = arg.span().expect("Argument without a span").clone();
+            arg_types.push((self.walk(arg), arg_span));
+        }
+        let sig = match funct_type {
+            Types::Funct(_, sig) => sig,
+            Types::Undefined(_) => return Types::Undefined(false),
+            t => {
+                self.error(ResolveErrorKind::NotCallable(t), &inner.span);
+                return Types::Undefined(false)
+            },
+        };
+        if sig.len() - 1 != arg_types.len() {
+            self.error(ResolveErrorKind::ArgCountMismatch { expected : sig.len() - 1, found : arg_types.len() }, &inner.span);
+        }
+        for (param_type, (arg_type, arg_span)) in sig[1..].iter().zip(arg_types) {
+            if !param_type.compatible(&arg_type) {
+                self.error(ResolveErrorKind::TypeMismatch { expected : param_type.clone(), found : arg_type }, &arg_span);
+            }
+        }
+        sig[0].with_assignable(false)
+    }
+
+    // The function is declared in the enclosing scope before its body is resolved, so that it can
+    // call itself. Its parameters and body are resolved in a new stack frame
+    fn walk_funct_decl(&mut self, inner : &mut ast::FunctDecl) -> Types {
+        let sig = self.funct_sig(&inner.params, &inner.ret_type, &inner.span);
+        let funct_type = Types::Funct(false, sig.clone());
+        let defined = !matches!(inner.body, Node::Empty);
+        // A function can be declared again with the same signature, but only defined once
+        let table = self.frame_tables.last().expect("No stack frame");
+        let prior = table.get_symbol(&inner.name).filter(|_| table.is_local(&inner.name));
+        if let Some(prior) = &prior {
+            match &prior.val_type {
+                Types::Funct(_, prior_sig) if *prior_sig != sig => {
+                    let expected = prior.val_type.clone();
+                    self.error(ResolveErrorKind::TypeMismatch { expected : expected, found : funct_type.clone() }, &inner.span);
+                },
+                Types::Funct(..) if !defined || !prior.is_defined.get() => (),
+                _ => self.error(ResolveErrorKind::Redeclaration(inner.name.clone()), &inner.span),
+            }
+        }
+        // Functions can't be assigned to after they are declared
+        let table = self.frame_tables.last_mut().expect("No stack frame");
+        table.insert(&inner.name, &funct_type, Modifiers { is_const : Some(inner.span.clone()), ..Modifiers::default() });
+        let symbol = table.get_symbol(&inner.name).expect("Function not declared");
+        symbol.is_defined.set(defined || prior.is_some_and(|prior| prior.is_defined.get()));
+        self.walk_funct_body(&inner.params, &sig, &mut inner.body, &inner.span);
+        funct_type
+    }
+
+    // A lambda is resolved like a function declaration, except that it has no name to declare.
+    // The variables it captures are recorded, since they must be stored along with it
+    fn walk_lambda(&mut self, inner : &mut ast::Lambda) -> Types {
+        let sig = self.funct_sig(&inner.params, &inner.ret_type, &inner.span);
+        inner.captures = self.walk_funct_body(&inner.params, &sig, &mut inner.body, &inner.span);
+        Types::Funct(false, sig)
     }
 
+    // The struct is declared before its fields are resolved, so that they can point to it. It can't
+    // contain itself by value, since it would have no finite size
+    fn walk_struct_decl(&mut self, inner : &mut ast::StructDecl) -> Types {
+        let table = self.frame_tables.last_mut().expect("No stack frame");
+        if table.is_local_struct(&inner.name) {
+            self.error(ResolveErrorKind::Redeclaration(inner.name.clone()), &inner.span);
+        }
+        let layout = Rc::new(StructLayout::new(&inner.name));
+        self.frame_tables.last_mut().expect("No stack frame").insert_struct(layout.clone());
+        let mut fields : Vec<(String, Types)> = Vec::new();
+        for field in &inner.fields {
+            let Some((field_type, name)) = self.field_type(field) else {
+                continue
+            };
+            let span = field.span().expect("Field without a span");
+            if fields.iter().any(|(other, _)| other == name) {
+                self.error(ResolveErrorKind::DuplicateField(name.to_string()), span);
+            } else if !field_type.is_complete() {
+                self.error(ResolveErrorKind::IncompleteField(name.to_string()), span);
+            } else {
+                fields.push((name.to_string(), field_type));
+            }
+        }
+        if !layout.define(fields) {
+            self.error(ResolveErrorKind::TooLarge(format!("struct {}", inner.name)), &inner.span);
+        }
+        Types::Undefined(false)
+    }
+
+    // Fields which are not given a value are zeroed
+    fn walk_struct_lit(&mut self, inner : &mut ast::StructLit) -> Types {
+        let layout = self.frame_tables.last().expect("No stack frame").get_struct(&inner.name);
+        let mut seen : Vec<&str> = Vec::new();
+        for field in inner.fields.iter_mut() {
+            let val_type = self.walk(&mut field.val);
+            let Some(layout) = &layout else {
+                continue
+            };
+            if seen.contains(&field.name.as_str()) {
+                self.error(ResolveErrorKind::DuplicateField(field.name.clone()), &field.span);
+                continue
+            }
+            seen.push(&field.name);
+            match layout.field(&field.name) {
+                Some(f) if !f.val_type.compatible(&val_type) => {
+                    self.error(ResolveErrorKind::TypeMismatch { expected : f.val_type, found : val_type }, &field.span);
+                },
+                Some(_) => (),
+                None => {
+                    let struct_type = Types::Struct(false, layout.clone());
+                    self.error(ResolveErrorKind::NoField { struct_type : struct_type, field : field.name.clone() }, &field.span);
+                },
+            }
+        }
+        match layout {
+            Some(layout) => Types::Struct(false, layout),
+            None => {
+                self.error(ResolveErrorKind::UndefinedStruct(inner.name.clone()), &inner.span);
+                Types::Undefined(false)
+            },
+        }
+    }
+
+    // a->x is the same as (*a).x. A field can be assigned to if the struct it belongs to can be
+    fn walk_member(&mut self, inner : &mut ast::Member) -> Types {
+        let record_type = match (&inner.op_type, self.walk(&mut inner.lhs)) {
+            (Token::Dot, t) => Some(t),
+            (_, Types::Pointer(_, t)) => Some(*t),
+            (_, Types::Undefined(_)) => Some(Types::Undefined(false)),
+            _ => None,
+        };
+        match record_type {
+            Some(Types::Struct(ass, layout)) => match layout.field(&inner.field) {
+                Some(field) => {
+                    inner.offset = Some(field.offset);
+                    field.val_type.with_assignable(ass)
+                },
+                None => {
+                    let struct_type = Types::Struct(false, layout);
+                    self.error(ResolveErrorKind::NoField { struct_type : struct_type, field : inner.field.clone() }, &inner.span);
+                    Types::Undefined(false)
+                },
+            },
+            Some(Types::Undefined(_)) => Types::Undefined(false),
+            _ => {
+                self.error(ResolveErrorKind::InvalidOperands(inner.op_type.clone()), &inner.span);
+                Types::Undefined(false)
+            },
+        }
+    }
+
+    // Each variant is declared as a constant in the enclosing scope. A variant with fields is
+    // declared as a function which constructs it from the values of its fields
+    fn walk_enum_decl(&mut self, inner : &mut ast::EnumDecl) -> Types {
+        let table = self.frame_tables.last_mut().expect("No stack frame");
+        if table.is_local_enum(&inner.name) {
+            self.error(ResolveErrorKind::Redeclaration(inner.name.clone()), &inner.span);
+        }
+        let layout = Rc::new(EnumLayout::new(&inner.name));
+        self.frame_tables.last_mut().expect("No stack frame").insert_enum(layout.clone());
+        let mut variants = Vec::new();
+        for variant in &inner.variants {
+            let fields : Vec<Types> = variant.fields.iter().map(|f| self.type_name_type(f)).collect();
+            if fields.iter().any(|f| !f.is_complete()) {
+                self.error(ResolveErrorKind::IncompleteVariant(variant.name.clone()), &variant.span);
+            }
+            variants.push((variant.name.clone(), fields));
+        }
+        let enum_type = Types::Enum(false, layout.clone());
+        for (variant, (name, fields)) in inner.variants.iter().zip(&variants) {
+            let val_type = match fields.is_empty() {
+                true => enum_type.clone(),
+                false => Types::Funct(false, [enum_type.clone()].into_iter().chain(fields.iter().cloned()).collect()),
+            };
+            if self.frame_tables.last().expect("No stack frame").is_local(name) {
+                self.error(ResolveErrorKind::Redeclaration(name.clone()), &variant.span);
+            }
+            self.frame_tables.last_mut().expect("No stack frame").insert(name, &val_type, Modifiers { is_const : Some(variant.span.clone()), ..Modifiers::default() });
+        }
+        if !layout.define(variants) {
+            self.error(ResolveErrorKind::TooLarge(format!("enum {}", inner.name)), &inner.span);
+        }
+        Types::Undefined(false)
+    }
+
+    // Each arm binds the fields of its variant in its own scope. As with an if, the arms should
+    // agree on the type of the value of the match. Every variant must be matched by some arm
+    fn walk_match(&mut self, inner : &mut ast::Match) -> Types {
+        let layout = match self.walk(&mut inner.val) {
+            Types::Enum(_, layout) => Some(layout),
+            Types::Undefined(_) => None,
+            t => {
+                self.error(ResolveErrorKind::NotAnEnum(t), inner.val.span().unwrap_or(&inner.span));
+                None
+            },
+        };
+        let mut matched : Vec<String> = Vec::new();
+        let mut wildcard = false;
+        let mut match_type = None;
+        for arm in inner.arms.iter_mut() {
+            if wildcard || arm.variant.as_ref().is_some_and(|v| matched.contains(v)) {
+                self.error(ResolveErrorKind::UnreachableArm, &arm.span);
+            }
+            // The fields of an unknown variant have already been reported, so bind them all
+            let unknown = vec![Types::Undefined(false); arm.bindings.len()];
+            let fields = match (&layout, &arm.variant) {
+                (_, None) => {
+                    wildcard = true;
+                    Vec::new()
+                },
+                (None, Some(_)) => unknown,
+                (Some(layout), Some(name)) => match layout.variant(name) {
+                    Some(variant) => {
+                        matched.push(name.clone());
+                        arm.tag = Some(variant.tag);
+                        variant.fields
+                    },
+                    None => {
+                        let enum_type = Types::Enum(false, layout.clone());
+                        self.error(ResolveErrorKind::NoVariant { enum_type : enum_type, variant : name.clone() }, &arm.span);
+                        unknown
+                    },
+                },
+            };
+            if fields.len() != arm.bindings.len() {
+                let variant = arm.variant.clone().unwrap_or("_".to_string());
+                self.error(ResolveErrorKind::PatternArity { variant : variant, expected : fields.len(), found : arm.bindings.len() }, &arm.span);
+            }
+            self.frame_tables.last_mut().expect("No stack frame").push_scope();
+            for (binding, field) in arm.bindings.iter().zip(&fields) {
+                // _ ignores a field
+                if binding != "_" {
+                    self.declare(binding, field, &arm.span);
+                }
+            }
+            let body_type = self.walk(&mut arm.body);
+            self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+            match_type = Some(match match_type {
+                None => body_type.with_assignable(false),
+                Some(t) => branch_type(t, body_type),
+            });
+        }
+        if let (Some(layout), false) = (&layout, wildcard) {
+            let missing : Vec<String> = layout.variants()
+                .into_iter()
+                .map(|v| v.name)
+                .filter(|name| !matched.contains(name))
+                .collect();
+            if !missing.is_empty() {
+                self.error(ResolveErrorKind::NonExhaustiveMatch(missing), &inner.span);
+            }
+        }
+        match_type.unwrap_or(Types::Undefined(false))
+    }
+
+    // Cases must be distinct integer constants, and at most one arm is the default. If the cases are
+    // dense, the arm for each value in their range is recorded, so a backend can jump straight to
+    // it rather than comparing the value against each case
+    fn walk_switch(&mut self, inner : &mut ast::Switch) -> Types {
+        let val_type = self.walk(&mut inner.val);
+        if !val_type.is_integer() {
+            self.error(ResolveErrorKind::NonIntegerSwitch(val_type.with_assignable(false)), inner.val.span().unwrap_or(&inner.span));
+        }
+        let mut cases : Vec<(i64, usize)> = Vec::new();
+        let mut default = false;
+        let mut switch_type = None;
+        for (i, arm) in inner.arms.iter_mut().enumerate() {
+            match &arm.cases {
+                None if default => self.error(ResolveErrorKind::DuplicateDefault, &arm.span),
+                None => default = true,
+                Some(values) => for value in values {
+                    // Values left out of a case have already been reported
+                    let Some(span) = value.span() else {
+                        continue
+                    };
+                    match case_value(value) {
+                        // Cases which could not be parsed have already been reported
+                        None if matches!(value, Node::Invalid(_)) => (),
+                        None => self.error(ResolveErrorKind::NonConstCase, span),
+                        Some(v) if cases.iter().any(|(other, _)| *other == v) => self.error(ResolveErrorKind::DuplicateCase(v), span),
+                        Some(v) => cases.push((v, i)),
+                    }
+                },
+            }
+            self.frame_tables.last_mut().expect("No stack frame").push_scope();
+            let body_type = self.walk(&mut arm.body);
+            self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+            switch_type = Some(match switch_type {
+                None => body_type.with_assignable(false),
+                Some(t) => branch_type(t, body_type),
+            });
+        }
+        inner.table = jump_table(&cases);
+        switch_type.unwrap_or(Types::Undefined(false))
+    }
+
+    // The names exported by the imported module become globals of the importing module. Imported
+    // variables are the same variables as in the module that exports them
+    fn walk_import(&mut self, inner : &mut ast::Import) -> Types {
+        if !self.frame_tables.last().expect("No stack frame").is_global() {
+            self.error(ResolveErrorKind::ImportNotGlobal, &inner.span);
+            return Types::Undefined(false)
+        }
+        // Modules which could not be loaded have already been reported
+        let Some(exports) = inner.module.as_ref().and_then(|path| self.module_exports.get(path)) else {
+            return Types::Undefined(false)
+        };
+        // Sort the names, so that conflicts are reported in a consistent order
+        let mut symbols : Vec<_> = exports.map.iter().map(|(name, symbol)| (name.clone(), symbol.clone())).collect();
+        let mut structs : Vec<_> = exports.structs.values().cloned().collect();
+        let mut enums : Vec<_> = exports.enums.values().cloned().collect();
+        let mut types : Vec<_> = exports.types.iter().map(|(name, aliased)| (name.clone(), aliased.clone())).collect();
+        symbols.sort_by(|a, b| a.0.cmp(&b.0));
+        types.sort_by(|a, b| a.0.cmp(&b.0));
+        structs.sort_by(|a, b| a.name.cmp(&b.name));
+        enums.sort_by(|a, b| a.name.cmp(&b.name));
+        for (name, symbol) in symbols {
+            if self.frame_tables.last().expect("No stack frame").is_local(&name) {
+                self.error(ResolveErrorKind::Redeclaration(name), &inner.span);
+            } else {
+                self.frame_tables.last_mut().expect("No stack frame").insert_symbol(&name, symbol);
+            }
+        }
+        for layout in structs {
+            if self.frame_tables.last().expect("No stack frame").is_local_struct(&layout.name) {
+                self.error(ResolveErrorKind::Redeclaration(layout.name.clone()), &inner.span);
+            } else {
+                self.frame_tables.last_mut().expect("No stack frame").insert_struct(layout);
+            }
+        }
+        for layout in enums {
+            if self.frame_tables.last().expect("No stack frame").is_local_enum(&layout.name) {
+                self.error(ResolveErrorKind::Redeclaration(layout.name.clone()), &inner.span);
+            } else {
+                self.frame_tables.last_mut().expect("No stack frame").insert_enum(layout);
+            }
+        }
+        for (name, aliased) in types {
+            if self.frame_tables.last().expect("No stack frame").is_local_type(&name) {
+                self.error(ResolveErrorKind::Redeclaration(name), &inner.span);
+            } else {
+                self.frame_tables.last_mut().expect("No stack frame").insert_type(&name, &aliased);
+            }
+        }
+        Types::Undefined(false)
+    }
+
+    // The alias is declared even if the aliased type is invalid, so that uses of it aren't reported
+    // as well
+    fn walk_typedef(&mut self, inner : &mut ast::TypeDef) -> Types {
+        let aliased = match &inner.aliased {
+            ast::Aliased::Decl(decl) => self.field_type(decl).map_or(Types::Undefined(false), |(aliased, _)| aliased),
+            ast::Aliased::TypeName(type_name) => self.type_name_type(type_name),
+        };
+        if self.frame_tables.last().expect("No stack frame").is_local_type(&inner.name) {
+            self.error(ResolveErrorKind::Redeclaration(inner.name.clone()), &inner.span);
+        }
+        self.frame_tables.last_mut().expect("No stack frame").insert_type(&inner.name, &aliased.with_assignable(false));
+        Types::Undefined(false)
+    }
+
+    // The size is known from the type of the operand, so it is calculated here rather than when the
+    // program is run. Like size_t in C, the size is an unsigned long
+    fn walk_sizeof(&mut self, inner : &mut ast::Sizeof) -> Types {
+        let operand_type = match &mut inner.arg {
+            ast::SizeofArg::TypeName(type_name) => self.type_name_type(type_name),
+            // The operand is never evaluated, so its declarations are kept to their own scope
+            ast::SizeofArg::Expr(expr) => {
+                self.frame_tables.last_mut().expect("No stack frame").push_scope();
+                let operand_type = self.walk(expr);
+                self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+                operand_type
+            },
+        };
+        if !operand_type.is_complete() {
+            self.error(ResolveErrorKind::IncompleteSizeof(operand_type.with_assignable(false)), &inner.span);
+        }
+        inner.size = Some(operand_type.size() / 8);
+        Types::ULong(false)
+    }
+
+    fn walk_cast(&mut self, inner : &mut ast::Cast) -> Types {
+        let target = self.type_name_type(&inner.type_name);
+        let val_type = self.walk(&mut inner.expr);
+        if !val_type.castable(&target) {
+            self.error(ResolveErrorKind::InvalidCast { from : val_type.with_assignable(false), to : target.clone() }, &inner.span);
+        }
+        target.with_assignable(false)
+    }
+
+    // Each branch is conditionally evaluated, so gets its own scope. The value of the if is the
+    // value of whichever branch is taken, so the branches should agree on its type
     fn walk_if(&mut self, inner : &mut ast::If) -> Types {
-        todo!()
+        self.walk(&mut inner.cond);
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        let t_type = self.walk(&mut inner.t_expr);
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        let f_type = self.walk(&mut inner.f_expr);
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        branch_type(t_type, f_type)
+    }
+
+    fn walk_while(&mut self, inner : &mut ast::While) -> Types {
+        self.walk(&mut inner.cond);
+        // The body is run a variable number of times, so its declarations are kept to its own scope
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        let loop_type = self.walk_loop_body(&inner.label, &mut inner.body);
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        loop_type
+    }
+
+    fn walk_for(&mut self, inner : &mut ast::For) -> Types {
+        // The loop gets its own scope for the init declarations, and the body gets a nested scope
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        self.walk(&mut inner.init);
+        self.walk(&mut inner.cond);
+        self.walk(&mut inner.step);
+        self.frame_tables.last_mut().expect("No stack frame").push_scope();
+        let loop_type = self.walk_loop_body(&inner.label, &mut inner.body);
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        self.frame_tables.last_mut().expect("No stack frame").pop_scope();
+        loop_type
+    }
+
+    // A break never evaluates to a value itself, since control leaves the enclosing loop
+    fn walk_break(&mut self, inner : &mut ast::Break) -> Types {
+        let val_type = match inner.val {
+            Node::Empty => None,
+            _ => Some(self.walk(&mut inner.val).with_assignable(false)),
+        };
+        let target = match &inner.label {
+            Some(l) => self.loops.iter().rposition(|ctx| ctx.label.as_ref() == Some(l)),
+            None => self.loops.len().checked_sub(1),
+        };
+        match (target, &inner.label) {
+            (None, Some(l)) => self.error(ResolveErrorKind::UndefinedLabel(l.clone()), &inner.span),
+            (None, None) => self.error(ResolveErrorKind::BreakOutsideLoop, &inner.span),
+            (Some(i), _) => if let Some(val_type) = val_type {
+                match &self.loops[i].break_type {
+                    Some(t) if *t != val_type => self.error(ResolveErrorKind::BreakValueMismatch, &inner.span),
+                    _ => self.loops[i].break_type = Some(val_type),
+                }
+            },
+        }
+        Types::Undefined(false)
+    }
+
+    fn walk_continue(&mut self, inner : &mut ast::Continue) -> Types {
+        match &inner.label {
+            Some(l) => if !self.loops.iter().any(|ctx| ctx.label.as_ref() == Some(l)) {
+                self.error(ResolveErrorKind::UndefinedLabel(l.clone()), &inner.span);
+            },
+            None => if self.loops.is_empty() {
+                self.error(ResolveErrorKind::ContinueOutsideLoop, &inner.span);
+            },
+        }
+        Types::Undefined(false)
+    }
+}
+
+// Return the type named by a sequence of type specifiers, or None if they don't name a type. The
+// specifiers can be in any order, as in C
+fn spec_type(specs : &[Token]) -> Option<Types> {
+    let (mut unsigned, mut longs, mut shorts) = (0, 0, 0);
+    let mut base = None;
+    for spec in specs {
+        match spec {
+            Token::UnsignedKey => unsigned += 1,
+            Token::LongKey => longs += 1,
+            Token::ShortKey => shorts += 1,
+            _ if base.is_some() => return None,
+            _ => base = Some(spec),
+        }
+    }
+    let unsigned = match unsigned {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+    let t = match (base, unsigned, longs, shorts) {
+        (None | Some(Token::IntKey), false, 0, 0) => Types::Int(false),
+        (None | Some(Token::IntKey), true, 0, 0) => Types::UInt(false),
+        (None | Some(Token::IntKey), false, 0, 1) => Types::Short(false),
+        (None | Some(Token::IntKey), true, 0, 1) => Types::UShort(false),
+        // long long is the same as long
+        (None | Some(Token::IntKey), false, 1 | 2, 0) => Types::Long(false),
+        (None | Some(Token::IntKey), true, 1 | 2, 0) => Types::ULong(false),
+        (Some(Token::CharKey), false, 0, 0) => Types::Char(false),
+        (Some(Token::CharKey), true, 0, 0) => Types::UChar(false),
+        (Some(Token::BoolKey), false, 0, 0) => Types::Bool(false),
+        (Some(Token::FloatKey), false, 0, 0) => Types::Float(false),
+        // long double is the same as double
+        (Some(Token::DoubleKey), false, 0 | 1, 0) => Types::Double(false),
+        _ => return None,
+    };
+    Some(t)
+}
+
+// Return the value of a switch case, which must be an integer constant. Constants too large for an
+// i64 wrap around, as they do when converted to a signed long
+fn case_value(n : &Node) -> Option<i64> {
+    match n {
+        Node::Int(i) => Some(i.val as i64),
+        Node::Char(c) => Some(c.val as i64),
+        Node::Bool(b) => Some(b.val as i64),
+        Node::PrefixOp(op) if op.op_type == Token::Sub => case_value(&op.rhs).map(i64::wrapping_neg),
+        Node::PrefixOp(op) if op.op_type == Token::Add => case_value(&op.rhs),
+        _ => None,
+    }
+}
+
+// The fewest cases a switch has a jump table for. With fewer, comparing against each is as fast
+const MIN_JUMP_TABLE_CASES : usize = 4;
+
+// Return the jump table for a switch, given the value of each case and the index of its arm. None
+// if there are too few cases, or if less than half the values in their range have a case
+fn jump_table(cases : &[(i64, usize)]) -> Option<ast::JumpTable> {
+    if cases.len() < MIN_JUMP_TABLE_CASES {
+        return None
+    }
+    let low = cases.iter().map(|(v, _)| *v).min()?;
+    let high = cases.iter().map(|(v, _)| *v).max()?;
+    let range = high.abs_diff(low).saturating_add(1);
+    if range > 2 * cases.len() as u64 {
+        return None
+    }
+    let mut arms = vec![None; range as usize];
+    for (v, arm) in cases {
+        arms[v.abs_diff(low) as usize] = Some(*arm);
+    }
+    Some(ast::JumpTable { low : low, arms : arms })
+}
+
+// Return the type of a value which is the value of one of two branches, such as those of an if.
+// Undefined if the branches don't agree on a type
+fn branch_type(t_type : Types, f_type : Types) -> Types {
+    match (t_type, f_type) {
+        (Types::Undefined(_), t) | (t, Types::Undefined(_)) => t.with_assignable(false),
+        (t, f) if t == f => t.with_assignable(false),
+        (t, f) if t.is_arithmetic() && f.is_arithmetic() => t.arithmetic_conversion(&f),
+        _ => Types::Undefined(false),
+    }
+}
+
+// Return the type specifiers at the start of a declaration, along with the declarator which
+// follows them
+fn decl_specs(decl : &ast::PrefixOp) -> (Vec<Token>, &Node) {
+    let mut specs = Vec::from([decl.op_type.clone()]);
+    let mut declarator = &decl.rhs;
+    while let Node::PrefixOp(op) = declarator {
+        if !op.op_type.is_type_spec() {
+            break;
+        }
+        specs.push(op.op_type.clone());
+        declarator = &op.rhs;
+    }
+    (specs, declarator)
+}
+
+// Return the type and name of the variable declared by a declarator with the given base type.
+// *p declares a pointer to the base type
+fn declarator_type(base : Types, declarator : &Node) -> Option<(Types, &str)> {
+    match declarator {
+        Node::Id(id) => Some((base, &id.name)),
+        Node::PrefixOp(op) if op.op_type == Token::Star => declarator_type(Types::pointer_to(base), &op.rhs),
+        _ => None,
+    }
+}
+
+// Return the modifiers applied by a function parameter declaration, such as const int a
+fn decl_modifiers(decl : &Node) -> Modifiers {
+    let Node::PrefixOp(op) = decl else {
+        return Modifiers::default()
+    };
+    let mut modifiers = decl_modifiers(&op.rhs);
+    match op.op_type {
+        Token::Const => modifiers.is_const = Some(op.span.clone()),
+        Token::Static => modifiers.is_static = true,
+        Token::Volatile => modifiers.is_volatile = true,
+        _ => (),
+    }
+    modifiers
+}
+
+// Check if a node declares a new variable, possibly with modifiers applied to it
+fn is_declaration(n : &Node) -> bool {
+    match n {
+        Node::PrefixOp(op) if op.op_type.is_modifier() => is_declaration(&op.rhs),
+        Node::PrefixOp(op) => op.op_type.is_type_spec(),
+        Node::ArrayDecl(_) => true,
+        _ => false,
     }
 }
 
+// Check if a node refers to a location in memory, which has an address. This is a variable or
+// function, a part of one, or what a pointer points to
+fn is_location(n : &Node) -> bool {
+    match n {
+        Node::Id(_) => true,
+        Node::Member(member) => member.op_type != Token::Dot || is_location(&member.lhs),
+        Node::InfixOp(op) => op.op_type == Token::LBrack,
+        Node::PrefixOp(op) => op.op_type == Token::Star,
+        _ => false,
+    }
+}
+
+// Return the name of the variable a location belongs to, such as a in a[0].x, or p in *p
+fn location_name(target : &Node) -> Option<&str> {
+    match target {
+        Node::Id(id) => Some(&id.name),
+        Node::Member(member) => location_name(&member.lhs),
+        Node::InfixOp(op) if op.op_type == Token::LBrack => location_name(&op.lhs),
+        Node::PrefixOp(op) if op.op_type == Token::Star => location_name(&op.rhs),
+        _ => None,
+    }
+}
+
+// Return the name of the variable a modifier is applied to, which may be declared by the target
+fn modified_name(target : &Node) -> Option<&str> {
+    match target {
+        Node::Id(id) => Some(&id.name),
+        Node::PrefixOp(op) if op.op_type.is_modifier() => modified_name(&op.rhs),
+        Node::PrefixOp(op) if op.op_type.is_type_spec() => {
+            declarator_type(Types::Undefined(false), decl_specs(op).1).map(|(_, name)| name)
+        },
+        Node::ArrayDecl(decl) => modified_name(&decl.decl),
+        Node::FunctDecl(f) => Some(&f.name),
+        _ => None,
+    }
+}
+
+// Check if an operator assigns to its left operand
+fn is_assignment(op : &Token) -> bool {
+    matches!(op,
+        Token::Assign
+        | Token::AddAss
+        | Token::SubAss
+        | Token::MulAss
+        | Token::DivAss
+        | Token::ModAss
+        | Token::AndAss
+        | Token::OrAss
+        | Token::XorAss
+        | Token::ShlAss
+        | Token::ShrAss)
+}
+
+// Return the type of the result of a binary operator, or None if it can't be applied to operands
+// of these types. Compound assignment operators give the type of the value assigned
+fn binary_type(op : &Token, lhs : &Types, rhs : &Types) -> Option<Types> {
+    match (op, lhs, rhs) {
+        // Pointer arithmetic moves the pointer by a number of elements, and arrays are treated as
+        // pointers to their first element
+        (Token::Add | Token::AddAss | Token::Sub | Token::SubAss, base @ (Types::Pointer(..) | Types::Array(..)), offset)
+            if offset.is_integer() => return Some(Types::Pointer(false, Box::new(base.element()?))),
+        (Token::Add, offset, base @ (Types::Pointer(..) | Types::Array(..)))
+            if offset.is_integer() => return Some(Types::Pointer(false, Box::new(base.element()?))),
+        // The difference between two pointers is the number of elements between them
+        (Token::Sub, Types::Pointer(_, t) | Types::Array(_, t, _), Types::Pointer(_, t_other) | Types::Array(_, t_other, _))
+            if t == t_other => return Some(Types::Int(false)),
+        _ => (),
+    }
+    // Operands are converted to a common type, following the usual arithmetic conversions
+    match op {
+        Token::Add | Token::AddAss
+            | Token::Sub | Token::SubAss
+            | Token::Star | Token::MulAss
+            | Token::Div | Token::DivAss if lhs.is_arithmetic() && rhs.is_arithmetic() => Some(lhs.arithmetic_conversion(rhs)),
+        Token::Mod | Token::ModAss
+            | Token::Amp | Token::AndAss
+            | Token::Pipe | Token::OrAss
+            | Token::Caret | Token::XorAss if lhs.is_integer() && rhs.is_integer() => Some(lhs.arithmetic_conversion(rhs)),
+        // The result of a shift has the type of the value being shifted
+        Token::Shl | Token::ShlAss
+            | Token::Shr | Token::ShrAss if lhs.is_integer() && rhs.is_integer() => Some(lhs.promote()),
+        Token::Equal | Token::NotEq
+            | Token::GT | Token::GE
+            | Token::LT | Token::LE if lhs.compatible(rhs) || rhs.compatible(lhs) => Some(Types::Int(false)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Parser, scanner::Scanner};
+
+    // Parse and resolve source, which should be free of syntax errors, and return the kinds of the
+    // errors found
+    fn resolve(source : &str) -> Vec<ResolveErrorKind> {
+        let mut scanner = Scanner::new(source.as_bytes(), "test.c");
+        let (mut ast, parse_errors) = Parser::new().gen_ast(&mut scanner);
+        assert!(parse_errors.is_empty(), "{parse_errors:?}");
+        let mut resolver = Resolver::new();
+        resolver.walk(&mut ast);
+        resolver.errors().iter().map(|e| e.kind.clone()).collect()
+    }
+
+    #[test]
+    fn declarations_on_the_right_of_logical_operators_are_scoped_to_it() {
+        assert_eq!(resolve("int a = 1; int b = a && (int c = 2); int d = a || c;"), [ResolveErrorKind::UndefinedName("c".to_string())]);
+    }
+
+    #[test]
+    fn while_loops_evaluate_to_their_body() {
+        assert_eq!(resolve("int a = 1; int x = while (a) a = 0;"), []);
+        assert_eq!(resolve("int a = 1; int x = while (a) \"s\";"), [ResolveErrorKind::TypeMismatch {
+            expected : Types::Int(false),
+            found : Types::Array(false, Box::new(Types::Char(false)), 2),
+        }]);
+        // The body has its own scope
+        assert_eq!(resolve("while (1) int y = 2; y = 3;"), [ResolveErrorKind::UndefinedName("y".to_string())]);
+    }
+
+    #[test]
+    fn for_loop_declarations_are_scoped_to_the_loop() {
+        assert_eq!(resolve("int i = 5; for (int i = 0; i < 3; i++) i; i = 1;"), []);
+        assert_eq!(resolve("for (int i = 0; i < 3; i++) i; int j = i;"), [ResolveErrorKind::UndefinedName("i".to_string())]);
+        // The body is nested in the scope of the init clause
+        assert_eq!(resolve("for (int i = 0; i < 3; i++) int i = 2;"), []);
+    }
+
+    #[test]
+    fn jumps_must_be_inside_their_loops() {
+        assert_eq!(resolve("int x = a: while (1) while (1) break a 2;"), []);
+        assert_eq!(resolve("break;"), [ResolveErrorKind::BreakOutsideLoop]);
+        assert_eq!(resolve("continue;"), [ResolveErrorKind::ContinueOutsideLoop]);
+        assert_eq!(resolve("while (1) { break 1; break 'c' }"), [ResolveErrorKind::BreakValueMismatch]);
+        // Loops outside a function can't be broken out of from inside it
+        assert_eq!(resolve("while (1) { int f() = { break; }; }"), [ResolveErrorKind::BreakOutsideLoop]);
+        assert_eq!(resolve("a: while (1) { int f() = { continue a; }; }"), [ResolveErrorKind::UndefinedLabel("a".to_string())]);
+    }
+
+    #[test]
+    fn calls_match_the_signature_of_the_function() {
+        let int = Types::Int(false);
+        let str = Types::Array(false, Box::new(Types::Char(false)), 2);
+        assert_eq!(resolve("int pow(int b, int e) = if (e == 0) 1 else b * pow(b, e - 1); int x = pow(2, 3);"), []);
+        assert_eq!(resolve("int f(int a) = a; f(1, 2);"), [ResolveErrorKind::ArgCountMismatch { expected : 1, found : 2 }]);
+        assert_eq!(resolve("int f(int a) = 1; f(\"s\");"), [ResolveErrorKind::TypeMismatch {
+            expected : int.clone(),
+            found : str.clone(),
+        }]);
+        assert_eq!(resolve("int x = 1; x(2);"), [ResolveErrorKind::NotCallable(int.with_assignable(true))]);
+        assert_eq!(resolve("int f() = { return \"s\"; };"), [ResolveErrorKind::TypeMismatch {
+            expected : int.clone(),
+            found : str.clone(),
+        }]);
+        assert_eq!(resolve("return 1;"), [ResolveErrorKind::ReturnOutsideFunction]);
+        // Parameters are local to the function
+        assert_eq!(resolve("int f(int a) = a; a;"), [ResolveErrorKind::UndefinedName("a".to_string())]);
+        assert_eq!(resolve("int f(int a, char a) = a;"), [ResolveErrorKind::Redeclaration("a".to_string())]);
+        // A function may have several prototypes, but only one definition
+        assert_eq!(resolve("int f(int a); int f(int a) = a; int f(int b); int x = f(1);"), []);
+        assert_eq!(resolve("int f() = 1; int f() = 2;"), [ResolveErrorKind::Redeclaration("f".to_string())]);
+        assert_eq!(resolve("int f(int a); char f(int a) = 'a';"), [ResolveErrorKind::TypeMismatch {
+            expected : Types::Funct(false, vec![int.clone(), int.clone()]),
+            found : Types::Funct(false, vec![Types::Char(false), int.clone()]),
+        }]);
+        assert_eq!(resolve("struct P { int x; }; struct P p; struct P f() = { return p; };"), []);
+        assert_eq!(resolve("int a = 1; int *f(int *p) = p; int *q = f(&a); *f(q) = 2;"), []);
+    }
+
+    #[test]
+    fn array_lengths_are_constant_or_inferred() {
+        assert_eq!(resolve("char s[] = \"hi\"; char t[3] = s; int a[2][3]; int x = a[1][2];"), []);
+        assert_eq!(resolve("int n = 3; int a[n];"), [ResolveErrorKind::NonConstArrayLength]);
+        assert_eq!(resolve("int a[];"), [ResolveErrorKind::UnknownArrayLength]);
+        assert_eq!(resolve("int a[2] = [1, 2, 3];"), [ResolveErrorKind::ArrayInitTooLong { len : 2, init_len : 3 }]);
+        assert_eq!(resolve("int a[] = [1, 'c'];"), [ResolveErrorKind::ArrayElementMismatch]);
+    }
+
+    #[test]
+    fn pointers_are_declared_taken_and_dereferenced() {
+        assert_eq!(resolve("int x = 1; int *p = &x; int **q = &p; **q = 2; int y = *p + 1;"), []);
+        assert_eq!(resolve("int x = 1; int *p = &(x + 1);"), [ResolveErrorKind::NotAddressable]);
+        assert_eq!(resolve("char c = 'a'; int y = *c;"), [ResolveErrorKind::InvalidOperands(Token::Star)]);
+    }
+
+    #[test]
+    fn unary_operators_promote_their_operands() {
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(found("char c = 'a'; int *p = -c;"), "int");
+        assert_eq!(found("long l = 1l; int *p = ~l;"), "long");
+        assert_eq!(found("double d = 1.0; int *p = !d;"), "int");
+        assert_eq!(resolve("double d = -1.5; int *p; int x = !p;"), []);
+        assert_eq!(resolve("double d = ~1.5;"), [ResolveErrorKind::InvalidOperands(Token::Tilde)]);
+        assert_eq!(resolve("int *p; int x = -p;"), [ResolveErrorKind::InvalidOperands(Token::Sub)]);
+    }
+
+    #[test]
+    fn conditional_branches_agree_on_a_type() {
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(found("int a = 1; int *p = a ? 1 : 2l;"), "long");
+        assert_eq!(found("int a = 1; int *p = a ? 'c' : 1.0;"), "double");
+        assert_eq!(resolve("int a = 1; int *p; int *q = a ? p : p;"), []);
+        // Each branch has its own scope
+        assert_eq!(resolve("int a = 1; a ? (int b = 1) : 2; b;"), [ResolveErrorKind::UndefinedName("b".to_string())]);
+    }
+
+    #[test]
+    fn const_variables_are_assigned_only_once() {
+        let const_assignment = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::ConstAssignment { name, .. }] => name.clone(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(resolve("const int y = 8; int x = 10; x = 5; static volatile x;"), []);
+        assert_eq!(const_assignment("const int y = 8; y = 4;"), "y");
+        assert_eq!(const_assignment("int x = 10; const x; x += 3;"), "x");
+        assert_eq!(const_assignment("int x = 10; const x; x++;"), "x");
+        assert_eq!(const_assignment("const int a[3] = [1, 2, 3]; a[0] = 5;"), "a");
+        assert_eq!(const_assignment("int a[2][2]; const a; a[1][0]++;"), "a");
+        assert_eq!(const_assignment("struct P { int x; }; const struct P p; p.x = 1;"), "p");
+        assert_eq!(const_assignment("const int a[3] = [1, 2, 3]; *a = 5;"), "a");
+        assert_eq!(resolve("int a[3]; int *p = a; const p; a[0] = 1; p[1] = 2; *p = 3; *(a + 2) = 4;"), []);
+        assert_eq!(resolve("const 3;"), [ResolveErrorKind::InvalidModifier(Token::Const)]);
+        assert_eq!(resolve("int f() = { pub int x = 1; };"), [ResolveErrorKind::PubNotGlobal]);
+    }
+
+    #[test]
+    fn scalar_types_and_arithmetic_conversions() {
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(found("unsigned long long a = 1ul; int *p = a;"), "unsigned long");
+        assert_eq!(found("short a = 1; short b = 2; int *p = a + b;"), "int");
+        assert_eq!(found("unsigned a = 1u; long b = 2l; int *p = a + b;"), "long");
+        assert_eq!(found("float a = 1.0f; double b = 2.0; int *p = a * b;"), "double");
+        assert_eq!(found("bool b = true; long double d = 1.0; int *p = b ? d : 1;"), "double");
+        assert_eq!(resolve("long char c;"), [ResolveErrorKind::InvalidTypeSpec(vec![Token::LongKey, Token::CharKey])]);
+        assert_eq!(resolve("unsigned unsigned x;"), [ResolveErrorKind::InvalidTypeSpec(vec![Token::UnsignedKey, Token::UnsignedKey])]);
+    }
+
+    #[test]
+    fn struct_fields_are_declared_once_and_accessed_by_name() {
+        let source = "struct P { int x; struct P *next; }; struct P p = P { x : 1 }; int y = p.next->next->x; p.x = 2;";
+        assert_eq!(resolve(source), []);
+        let no_field = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::NoField { field, .. }] => field.clone(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(no_field("struct P { int x; }; struct P p; p.y;"), "y");
+        assert_eq!(no_field("struct P { int x; }; struct P p = P { y : 1 };"), "y");
+        assert_eq!(resolve("struct P { int x; char x; };"), [ResolveErrorKind::DuplicateField("x".to_string())]);
+        assert_eq!(resolve("struct P { int x; }; struct P p = P { x : 1, x : 2 };"), [ResolveErrorKind::DuplicateField("x".to_string())]);
+        assert_eq!(resolve("struct P { struct P inner; };"), [ResolveErrorKind::IncompleteField("inner".to_string())]);
+        assert_eq!(resolve("struct Q q;"), [ResolveErrorKind::UndefinedStruct("Q".to_string())]);
+        assert_eq!(resolve("int x = 1; x.y;"), [ResolveErrorKind::InvalidOperands(Token::Dot)]);
+    }
+
+    #[test]
+    fn matches_cover_each_variant_once() {
+        let shape = "enum Shape { Circle(int), Rect(int, int), Empty }; enum Shape s = Circle(1);";
+        let with_shape = |m : &str| resolve(&format!("{shape} {m}"));
+        assert_eq!(with_shape("int a = match (s) { Circle(r) => r, Rect(w, _) => w, Empty => 0 };"), []);
+        assert_eq!(with_shape("int a = match (s) { Circle(r) => r, _ => 0 };"), []);
+        assert_eq!(with_shape("match (s) { Circle(r) => r };"), [ResolveErrorKind::NonExhaustiveMatch(vec!["Rect".to_string(), "Empty".to_string()])]);
+        assert_eq!(with_shape("match (s) { _ => 0, Empty => 1 };"), [ResolveErrorKind::UnreachableArm]);
+        assert_eq!(with_shape("match (s) { Circle(a, b) => 0, _ => 1 };"), [ResolveErrorKind::PatternArity { variant : "Circle".to_string(), expected : 1, found : 2 }]);
+        assert!(matches!(with_shape("match (s) { Square => 0, _ => 1 };").as_slice(), [ResolveErrorKind::NoVariant { variant, .. }] if variant == "Square"));
+        assert_eq!(with_shape("Rect(1);"), [ResolveErrorKind::ArgCountMismatch { expected : 2, found : 1 }]);
+        assert_eq!(resolve("int x = 1; match (x) { _ => 0 };"), [ResolveErrorKind::NotAnEnum(Types::Int(true))]);
+        assert_eq!(resolve("enum E { A(enum E) };"), [ResolveErrorKind::IncompleteVariant("A".to_string())]);
+        assert_eq!(resolve("enum F f;"), [ResolveErrorKind::UndefinedEnum("F".to_string())]);
+    }
+
+    #[test]
+    fn lambdas_record_the_variables_they_capture() {
+        let source = "int z = 1; int(int) f(int a, int b) = int (int c) => a + c + z + a";
+        let mut scanner = Scanner::new(source.as_bytes(), "test.c");
+        let (mut ast, _) = Parser::new().gen_ast(&mut scanner);
+        let mut resolver = Resolver::new();
+        resolver.walk(&mut ast);
+        assert!(resolver.errors().is_empty(), "{:?}", resolver.errors());
+        let Node::Statement(s) = &ast else { panic!("Not a statement") };
+        let Node::FunctDecl(f) = &s.next else { panic!("Not a function declaration") };
+        let Node::Lambda(l) = &f.body else { panic!("Not a lambda") };
+        assert_eq!(l.captures, ["a"]);
+        assert_eq!(resolve("int(int) f = int (int c) => c; c;"), [ResolveErrorKind::UndefinedName("c".to_string())]);
+        assert_eq!(resolve("int *p; int(int) f = int (int c) => p;"), [ResolveErrorKind::TypeMismatch {
+            expected : Types::Int(false),
+            found : Types::Pointer(false, Box::new(Types::Int(false))),
+        }]);
+    }
+
+    #[test]
+    fn type_aliases_name_the_aliased_type() {
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(resolve("typedef int *IntPtr; int x = 1; IntPtr p = &x; typedef int Triple[3]; Triple t; int y = t[2];"), []);
+        assert_eq!(found("type Num = unsigned long; Num n = 1ul; int *p = n;"), "unsigned long");
+        assert_eq!(found("typedef int(int) F; F f = int(int c) => c; int *p = f;"), "int(int)");
+        assert_eq!(resolve("type T = int; type T = char;"), [ResolveErrorKind::Redeclaration("T".to_string())]);
+        assert_eq!(resolve("type T = struct Q;"), [ResolveErrorKind::UndefinedStruct("Q".to_string())]);
+    }
+
+    #[test]
+    fn sizes_are_known_when_resolving() {
+        // Return the size in bytes calculated for the sizeof expression ending source
+        let size = |source : &str| {
+            let mut scanner = Scanner::new(source.as_bytes(), "test.c");
+            let (mut ast, _) = Parser::new().gen_ast(&mut scanner);
+            Resolver::new().walk(&mut ast);
+            let mut last = &ast;
+            while let Node::Statement(s) = last {
+                last = &s.next;
+            }
+            match last {
+                Node::Sizeof(s) => s.size,
+                _ => panic!("Not a sizeof expression"),
+            }
+        };
+        assert_eq!(size("sizeof(long)"), Some(8));
+        assert_eq!(size("sizeof(char *)"), Some(4));
+        assert_eq!(size("int a[5]; sizeof a"), Some(20));
+        assert_eq!(size("struct P { char c; long l; }; sizeof(struct P)"), Some(16));
+    }
+
+    #[test]
+    fn casts_convert_between_scalar_types() {
+        assert_eq!(resolve("double d = 1.5; int *p = (int *) (long) d; char c = (char) p; bool b = (bool) c;"), []);
+        let struct_p = "struct P { int x; }; struct P s;";
+        assert!(matches!(resolve(&format!("{struct_p} (int) s;")).as_slice(), [ResolveErrorKind::InvalidCast { .. }]));
+        assert!(matches!(resolve("double d = 1.5; (int *) d;").as_slice(), [ResolveErrorKind::InvalidCast { .. }]));
+        // A cast gives a value rather than a location
+        assert_eq!(resolve("int x = 1; long *p = &(long) x;"), [ResolveErrorKind::NotAddressable]);
+    }
+
+    #[test]
+    fn switch_cases_are_distinct_integer_constants() {
+        assert_eq!(resolve("int x = 1; switch (x) { case 1, -1: 2; case 'a': 3; default: 4 }"), []);
+        assert_eq!(resolve("int x = 1; switch (x) { case 1: 2; case 2, 1: 3 }"), [ResolveErrorKind::DuplicateCase(1)]);
+        assert_eq!(resolve("int x = 1; switch (x) { default: 2; default: 3 }"), [ResolveErrorKind::DuplicateDefault]);
+        assert_eq!(resolve("int x = 1; switch (x) { case x: 2 }"), [ResolveErrorKind::NonConstCase]);
+    }
+
+    #[test]
+    fn large_unsuffixed_constants_are_long() {
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(found("int *p = 2147483647;"), "int");
+        assert_eq!(found("int *p = 2147483648;"), "long");
+        assert_eq!(found("int *p = -2147483648;"), "long");
+        assert_eq!(found("int *p = 0x80000000;"), "unsigned int");
+        assert_eq!(found("int *p = 0xFFFFFFFF;"), "unsigned int");
+        assert_eq!(found("int *p = 0x100000000;"), "long");
+        assert_eq!(found("int *p = 0xFFFFFFFFFFFFFFFF;"), "unsigned long");
+    }
+
+    #[test]
+    fn pointers_to_elements_and_through_pointers() {
+        assert_eq!(resolve("int a[3]; int *p = &a[1]; *p++ = 2; int x = *p;"), []);
+        assert_eq!(resolve("int *p = &3;"), [ResolveErrorKind::NotAddressable]);
+        assert_eq!(resolve("const int x = 3; int *p = &x; int f() = 1; long n = sizeof(&f);"), []);
+        assert_eq!(resolve("const int x = 3; *&x = 4;"), [ResolveErrorKind::NotAssignable]);
+        let found = |source| match resolve(source).as_slice() {
+            [ResolveErrorKind::TypeMismatch { found, .. }] => found.to_string(),
+            errors => panic!("{errors:?}"),
+        };
+        assert_eq!(found("const int x = 3; char c = &x;"), "const int*");
+        assert_eq!(found("int *p; const p; char c = &p;"), "int* const*");
+        assert_eq!(found("int x = 3; char c = &x;"), "int*");
+        assert_eq!(resolve("int x = 1; int y = *x;"), [ResolveErrorKind::InvalidOperands(Token::Star)]);
+    }
+
+    #[test]
+    fn arrays_too_large_to_represent_are_reported() {
+        let too_large = [ResolveErrorKind::TooLarge("a".to_string())];
+        assert_eq!(resolve("int a[0xFFFFFFFFFFFFFFFFul];"), too_large);
+        assert_eq!(resolve("long a[0x4000000000000000ul][2];"), too_large);
+        assert_eq!(resolve("char a[0x100000000ul]; long n = sizeof(a);"), []);
+    }
+
+    #[test]
+    fn structs_too_large_to_represent_are_reported() {
+        let too_large = [ResolveErrorKind::TooLarge("struct P".to_string())];
+        assert_eq!(resolve("struct P { long a[0x3FFFFFFFFFFFFFFul]; long b[0x3FFFFFFFFFFFFFFul]; };"), too_large);
+        assert_eq!(resolve("struct P { bool a[0x1FFFFFFFFFFFFFFFul]; int b; };"), too_large);
+        assert_eq!(resolve("struct P { long a[0x3FFFFFFFFFFFFFEul]; long b; }; long n = sizeof(struct P);"), []);
+    }
+
+    #[test]
+    fn enums_too_large_to_represent_are_reported() {
+        let too_large = [ResolveErrorKind::TooLarge("enum E".to_string())];
+        assert_eq!(resolve("struct P { long a[0x3FFFFFFFFFFFFFFul]; }; enum E { A(struct P), B };"), too_large);
+        assert_eq!(resolve("struct P { long a[0x3FFFFFFFFFFFFFEul]; }; enum E { A(struct P), B(int) };"), []);
+    }
+
+    #[test]
+    fn function_values_are_stored_and_returned_by_function_types() {
+        let source = "int z = 1; int(int) adder(int a) = int(int c) => z + a + c; int(int) g = adder(2); int x = g(3);";
+        assert_eq!(resolve(source), []);
+        assert_eq!(resolve("int(int)(int) f = int(int)(int a) => int(int b) => a + b; int x = f(1)(2);"), []);
+        assert_eq!(resolve("int(int) g = 3;"), [ResolveErrorKind::TypeMismatch {
+            expected : Types::Funct(false, vec![Types::Int(false), Types::Int(false)]),
+            found : Types::Int(false),
+        }]);
+        assert_eq!(resolve("int(long char) g;"), [ResolveErrorKind::InvalidTypeSpec(vec![Token::LongKey, Token::CharKey])]);
+    }
+
+    #[test]
+    fn dense_switches_have_jump_tables() {
+        // Too few cases to be worth a table
+        assert!(jump_table(&[(1, 0), (2, 1), (3, 2)]).is_none());
+        // Cases for fewer than half the values in their range
+        assert!(jump_table(&[(0, 0), (1, 1), (2, 2), (9, 3)]).is_none());
+        assert!(jump_table(&[(i64::MIN, 0), (0, 1), (1, 2), (i64::MAX, 3)]).is_none());
+        let table = jump_table(&[(-1, 0), (0, 0), (2, 1), (4, 2), (5, 3)]).expect("No jump table");
+        assert_eq!(table.low, -1);
+        assert_eq!(table.arms, [Some(0), Some(0), None, Some(1), None, Some(2), Some(3)]);
+    }
+}
@@ -1,46 +1,442 @@
-use std::{collections::HashMap, ops::Deref, rc::Rc};
+use std::{cell::{Cell, RefCell}, collections::HashMap, fmt, ops::Deref, rc::Rc};
+
+use crate::scanner::span::Span;
 
 // Formal list of defined types in the language
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 // All types have an additional boolean for whether it is assignable
 pub enum Types { 
     Undefined(bool),
-    Int(bool),
+    Bool(bool),
     Char(bool),
+    UChar(bool),
+    Short(bool),
+    UShort(bool),
+    Int(bool),
+    UInt(bool),
+    Long(bool),
+    ULong(bool),
+    Float(bool),
+    Double(bool),
     Pointer(bool, Box<Types>),
-    Funct(bool, Vec<Types>),
+    Array(bool, Box<Types>, usize), // Element type and length
+    Funct(bool, Vec<Types>), // Return type, followed by the parameter types
+    Struct(bool, Rc<StructLayout>),
+    Enum(bool, Rc<EnumLayout>),
 }
 
 impl Types {
     pub fn assignable(&self) -> bool {
-        match self {
+        *match self {
             Types::Undefined(ass) => ass,
-            Types::Int(ass) => ass,
+            Types::Bool(ass) => ass,
             Types::Char(ass) => ass,
+            Types::UChar(ass) => ass,
+            Types::Short(ass) => ass,
+            Types::UShort(ass) => ass,
+            Types::Int(ass) => ass,
+            Types::UInt(ass) => ass,
+            Types::Long(ass) => ass,
+            Types::ULong(ass) => ass,
+            Types::Float(ass) => ass,
+            Types::Double(ass) => ass,
             Types::Pointer(ass, _) => ass,
+            Types::Array(ass, _, _) => ass,
             Types::Funct(ass, _) => ass,
-        }.clone()
+            Types::Struct(ass, _) => ass,
+            Types::Enum(ass, _) => ass,
+        }
+    }
+
+    // Return a copy of this type with the assignable flag set to ass
+    pub fn with_assignable(&self, ass : bool) -> Types {
+        match self {
+            Types::Undefined(_) => Types::Undefined(ass),
+            Types::Bool(_) => Types::Bool(ass),
+            Types::Char(_) => Types::Char(ass),
+            Types::UChar(_) => Types::UChar(ass),
+            Types::Short(_) => Types::Short(ass),
+            Types::UShort(_) => Types::UShort(ass),
+            Types::Int(_) => Types::Int(ass),
+            Types::UInt(_) => Types::UInt(ass),
+            Types::Long(_) => Types::Long(ass),
+            Types::ULong(_) => Types::ULong(ass),
+            Types::Float(_) => Types::Float(ass),
+            Types::Double(_) => Types::Double(ass),
+            Types::Pointer(_, t) => Types::Pointer(ass, t.clone()),
+            Types::Array(_, t, len) => Types::Array(ass, t.clone(), *len),
+            Types::Funct(_, sig) => Types::Funct(ass, sig.clone()),
+            Types::Struct(_, layout) => Types::Struct(ass, layout.clone()),
+            Types::Enum(_, layout) => Types::Enum(ass, layout.clone()),
+        }
+    }
+
+    // Return the type of a pointer to a value of type t, which can be assigned to through it
+    pub fn pointer_to(t : Types) -> Types {
+        Types::Pointer(false, Box::new(t.with_assignable(true)))
+    }
+
+    // Return the type of the element a pointer points to, or of the elements of an array. The
+    // elements of an array can only be assigned to if the array itself can, as in a const array
+    pub fn element(&self) -> Option<Types> {
+        match self {
+            Types::Pointer(_, t) => Some(*t.clone()),
+            Types::Array(ass, t, _) => Some(t.with_assignable(*ass)),
+            _ => None,
+        }
+    }
+
+    // Check if values of this type can be used in arithmetic
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_integer() || matches!(self, Types::Float(_) | Types::Double(_))
+    }
+
+    // Check if values of this type are whole numbers, and so can be used in bitwise operations
+    pub fn is_integer(&self) -> bool {
+        matches!(self,
+            Types::Undefined(_)
+            | Types::Bool(_)
+            | Types::Char(_)
+            | Types::UChar(_)
+            | Types::Short(_)
+            | Types::UShort(_)
+            | Types::Int(_)
+            | Types::UInt(_)
+            | Types::Long(_)
+            | Types::ULong(_))
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self, Types::UChar(_) | Types::UShort(_) | Types::UInt(_) | Types::ULong(_))
+    }
+
+    // The conversion rank of an integer type. Integers of lower rank are converted to the higher
+    // rank when they are combined
+    fn rank(&self) -> u8 {
+        match self {
+            Types::Bool(_) => 0,
+            Types::Char(_) | Types::UChar(_) => 1,
+            Types::Short(_) | Types::UShort(_) => 2,
+            Types::Long(_) | Types::ULong(_) => 4,
+            _ => 3,
+        }
+    }
+
+    // Return the type an arithmetic value is promoted to before an operator is applied to it.
+    // Integers smaller than an int are promoted to int
+    pub fn promote(&self) -> Types {
+        if self.is_integer() && self.rank() < 3 && !matches!(self, Types::Undefined(_)) {
+            Types::Int(false)
+        } else {
+            self.with_assignable(false)
+        }
+    }
+
+    // Return the common type two arithmetic values are converted to when they are combined by an
+    // operator, following the usual arithmetic conversions of C
+    pub fn arithmetic_conversion(&self, other : &Types) -> Types {
+        let (lhs, rhs) = (self.promote(), other.promote());
+        match (&lhs, &rhs) {
+            (Types::Undefined(_), _) | (_, Types::Undefined(_)) => Types::Undefined(false),
+            (Types::Double(_), _) | (_, Types::Double(_)) => Types::Double(false),
+            (Types::Float(_), _) | (_, Types::Float(_)) => Types::Float(false),
+            // A long can hold every unsigned int, so only integers of the same rank convert to
+            // unsigned
+            _ if lhs.rank() == rhs.rank() => if rhs.is_unsigned() {rhs} else {lhs},
+            _ => if lhs.rank() > rhs.rank() {lhs} else {rhs},
+        }
+    }
+
+    // Check if a value of type other can be stored in a location of this type. Arithmetic types
+    // convert between each other, and arrays convert to pointers to their elements. Undefined
+    // types have already been reported, so are compatible with anything
+    pub fn compatible(&self, other : &Types) -> bool {
+        match (self, other) {
+            (Types::Undefined(_), _) | (_, Types::Undefined(_)) => true,
+            (Types::Pointer(_, t), Types::Array(_, t_other, _)) => t == t_other,
+            // Initializers longer than the array are reported separately
+            (Types::Array(_, t, _), Types::Array(_, t_other, _)) => t == t_other,
+            _ => (self.is_arithmetic() && other.is_arithmetic()) || self == other,
+        }
+    }
+
+    // Check if a value of this type can be explicitly converted to target. Besides the conversions
+    // assignment allows, pointers convert to other pointers and to and from integers. A struct or
+    // enum can only be cast to its own type, and nothing can be cast to an array or function
+    pub fn castable(&self, target : &Types) -> bool {
+        match (self, target) {
+            (Types::Undefined(_), _) | (_, Types::Undefined(_)) => true,
+            (_, Types::Array(..) | Types::Funct(..)) => false,
+            (Types::Pointer(..) | Types::Array(..), Types::Pointer(..)) => true,
+            // Arrays are converted to pointers to their elements first
+            (Types::Pointer(..) | Types::Array(..), t) | (t, Types::Pointer(..)) => t.is_integer(),
+            _ => (self.is_arithmetic() && target.is_arithmetic()) || self == target,
+        }
+    }
+
+    // Return the size of a value of this type, in bits. Arrays too large for this to be represented
+    // are reported when they are declared, so the size saturates rather than overflowing
+    pub fn size(&self) -> u64 {
+        let i = match self {
+            Types::Undefined(_) => return 0,
+            Types::Array(_, t, len) => return t.size().saturating_mul(*len as u64),
+            Types::Struct(_, layout) => return layout.size(),
+            Types::Enum(_, layout) => return layout.size(),
+            Types::Int(_) | Types::UInt(_) => 0,
+            Types::Char(_) | Types::UChar(_) => 1,
+            Types::Pointer(..) => 2,
+            Types::Funct(..) => 3,
+            Types::Bool(_) => 4,
+            Types::Short(_) | Types::UShort(_) => 5,
+            Types::Long(_) | Types::ULong(_) => 6,
+            Types::Float(_) => 7,
+            Types::Double(_) => 8,
+        };
+        SIZES[i] as u64
+    }
+
+    // Check if the size of values of this type is known. A struct is incomplete until its fields
+    // have been defined
+    pub fn is_complete(&self) -> bool {
+        match self {
+            Types::Array(_, t, _) => t.is_complete(),
+            Types::Struct(_, layout) => layout.is_defined(),
+            Types::Enum(_, layout) => layout.is_defined(),
+            _ => true,
+        }
+    }
+
+    // Return the alignment of a value of this type, in bits. Values are stored at offsets which are
+    // a multiple of their alignment
+    pub fn align(&self) -> u64 {
+        match self {
+            Types::Array(_, t, _) => t.align(),
+            Types::Struct(_, layout) => layout.align(),
+            Types::Enum(_, layout) => layout.align(),
+            t => t.size().max(8),
+        }
     }
 }
 
 impl PartialEq for Types {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Types::Undefined(_), Types::Undefined(_)) => true,
-            (Types::Int(_), Types::Int(_)) => true,
-            (Types::Char(_), Types::Char(_)) => true,
             (Types::Pointer(_, type_self), Types::Pointer(_, type_other)) => type_self.eq(type_other),
+            (Types::Array(_, type_self, len_self), Types::Array(_, type_other, len_other)) => type_self.eq(type_other) && len_self == len_other,
             (Types::Funct(_, sig_self), Types::Funct(_, sig_other)) => sig_self == sig_other,
-            _ => false,
+            // Structs are only equal to themselves, even if another struct has the same fields
+            (Types::Struct(_, layout_self), Types::Struct(_, layout_other)) => Rc::ptr_eq(layout_self, layout_other),
+            (Types::Enum(_, layout_self), Types::Enum(_, layout_other)) => Rc::ptr_eq(layout_self, layout_other),
+            // Other types are equal if they are the same variant
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Types {}
+
+impl fmt::Display for Types {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Types::Undefined(_) => write!(f, "undefined"),
+            Types::Bool(_) => write!(f, "bool"),
+            Types::Char(_) => write!(f, "char"),
+            Types::UChar(_) => write!(f, "unsigned char"),
+            Types::Short(_) => write!(f, "short"),
+            Types::UShort(_) => write!(f, "unsigned short"),
+            Types::Int(_) => write!(f, "int"),
+            Types::UInt(_) => write!(f, "unsigned int"),
+            Types::Long(_) => write!(f, "long"),
+            Types::ULong(_) => write!(f, "unsigned long"),
+            Types::Float(_) => write!(f, "float"),
+            Types::Double(_) => write!(f, "double"),
+            // Functions can't be assigned to, but aren't written as const
+            Types::Pointer(_, t) if t.assignable() || matches!(**t, Types::Funct(..) | Types::Undefined(_)) => write!(f, "{t}*"),
+            Types::Pointer(_, t) if matches!(**t, Types::Pointer(..)) => write!(f, "{t} const*"),
+            Types::Pointer(_, t) => write!(f, "const {t}*"),
+            Types::Array(_, t, len) => write!(f, "{t}[{len}]"),
+            Types::Funct(_, sig) => write!(f, "{}({})", sig[0], sig[1..]
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")),
+            Types::Struct(_, layout) => write!(f, "struct {}", layout.name),
+            Types::Enum(_, layout) => write!(f, "enum {}", layout.name),
+        }
+    }
+}
+
+// A field of a struct, and its offset from the start of the struct in bits
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name : String,
+    pub offset : u64,
+    pub val_type : Types,
+}
+
+// The fields of a struct, and where each is stored within it. A struct is created before its fields
+// are defined, so that they can point to the struct itself
+pub struct StructLayout {
+    pub name : String,
+    fields : RefCell<Option<Vec<Field>>>, // None until the struct is defined
+    size : Cell<u64>,
+    align : Cell<u64>,
+}
+
+impl StructLayout {
+    pub fn new(name : &str) -> Self {
+        StructLayout {
+            name : name.to_string(),
+            fields : RefCell::new(None),
+            size : Cell::new(0),
+            align : Cell::new(8),
+        }
+    }
+
+    // Lay out the fields in the order they are given. Each field is placed at the next offset which
+    // is a multiple of its alignment, and the size of the struct is padded to a multiple of the
+    // largest alignment, so that the fields stay aligned in arrays of the struct. Returns false if
+    // the struct is too large for its size in bits to be represented, in which case the fields
+    // that don't fit are dropped
+    pub fn define(&self, fields : Vec<(String, Types)>) -> bool {
+        let mut offset : u64 = 0;
+        let mut align : u64 = 8;
+        let mut laid_out = Vec::new();
+        let mut fits = true;
+        for (name, val_type) in fields {
+            let field_align = val_type.align();
+            align = align.max(field_align);
+            let Some((start, end)) = offset.checked_next_multiple_of(field_align)
+                .and_then(|start| Some((start, start.checked_add(val_type.size())?))) else {
+                fits = false;
+                break
+            };
+            laid_out.push(Field { name : name, offset : start, val_type : val_type });
+            offset = end;
+        }
+        let size = offset.checked_next_multiple_of(align);
+        self.size.set(size.unwrap_or(offset));
+        self.align.set(align);
+        *self.fields.borrow_mut() = Some(laid_out);
+        fits && size.is_some()
+    }
+
+    pub fn is_defined(&self) -> bool {
+        self.fields.borrow().is_some()
+    }
+
+    pub fn field(&self, name : &str) -> Option<Field> {
+        self.fields.borrow().as_ref()?.iter().find(|f| f.name == name).cloned()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size.get()
+    }
+
+    pub fn align(&self) -> u64 {
+        self.align.get()
+    }
+}
+
+// Only the name is printed, since a struct may contain pointers to itself
+impl fmt::Debug for StructLayout {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "struct {}", self.name)
+    }
+}
+
+// A variant of an enum. The tag identifies which variant a value of the enum holds
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub name : String,
+    pub tag : usize,
+    pub fields : Vec<Types>,
+}
+
+// The variants of an enum. A value of an enum is stored as the int tag of its variant, followed by
+// the fields of that variant. The variants share the space after the tag, so the enum is as large
+// as its largest variant
+pub struct EnumLayout {
+    pub name : String,
+    variants : RefCell<Option<Vec<Variant>>>, // None until the enum is defined
+    size : Cell<u64>,
+    align : Cell<u64>,
+}
+
+impl EnumLayout {
+    pub fn new(name : &str) -> Self {
+        EnumLayout {
+            name : name.to_string(),
+            variants : RefCell::new(None),
+            size : Cell::new(0),
+            align : Cell::new(8),
         }
     }
+
+    // Tag the variants in the order they are given, and lay out the fields of each after the tag,
+    // in the same way as the fields of a struct. Returns false if a variant is too large for the
+    // size of the enum in bits to be represented
+    pub fn define(&self, variants : Vec<(String, Vec<Types>)>) -> bool {
+        let tag_size = Types::Int(false).size();
+        let mut size : Option<u64> = Some(tag_size);
+        let mut align : u64 = tag_size;
+        let mut tagged = Vec::new();
+        for (tag, (name, fields)) in variants.into_iter().enumerate() {
+            let mut offset = Some(tag_size);
+            for field in &fields {
+                offset = offset.and_then(|o| o.checked_next_multiple_of(field.align())?.checked_add(field.size()));
+                align = align.max(field.align());
+            }
+            size = size.zip(offset).map(|(size, offset)| size.max(offset));
+            tagged.push(Variant { name : name, tag : tag, fields : fields });
+        }
+        let size = size.and_then(|size| size.checked_next_multiple_of(align));
+        self.size.set(size.unwrap_or(u64::MAX));
+        self.align.set(align);
+        *self.variants.borrow_mut() = Some(tagged);
+        size.is_some()
+    }
+
+    pub fn is_defined(&self) -> bool {
+        self.variants.borrow().is_some()
+    }
+
+    pub fn variant(&self, name : &str) -> Option<Variant> {
+        self.variants.borrow().as_ref()?.iter().find(|v| v.name == name).cloned()
+    }
+
+    // Return the variants in tag order. Empty if the enum is not defined yet
+    pub fn variants(&self) -> Vec<Variant> {
+        self.variants.borrow().clone().unwrap_or_default()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size.get()
+    }
+
+    pub fn align(&self) -> u64 {
+        self.align.get()
+    }
+}
+
+// Only the name is printed, since an enum may contain pointers to itself
+impl fmt::Debug for EnumLayout {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "enum {}", self.name)
+    }
 }
 
-const SIZES : [u8; 4] = [
+// Sizes of the types in bits. Unsigned types are the same size as their signed counterparts
+const SIZES : [u8; 9] = [
     32, // Int
     32, // Char
     32, // Ptr
     32, // Funct
+    8, // Bool
+    16, // Short
+    64, // Long
+    32, // Float
+    64, // Double
 ];
 
 #[derive(Clone)]
@@ -73,18 +469,33 @@ impl Deref for SymbolPtr {
 // value of a scope before it is assigned to a variable.
 // structs associated to the symbol table. 
 pub struct Symbol {
+    #[allow(dead_code)] // Not read yet
     pub id : Option<String>,
     // The id of the function that owns the stack frame this var belongs to. None if global frame
     pub frame_id : usize, 
+    #[allow(dead_code)] // Not read yet
     pub offset : usize, // offset from frame pointer.
-    pub is_const : bool,
-    pub is_captured : bool,
+    pub modifiers : RefCell<Modifiers>, // Modifiers can be applied after the symbol is declared
+    pub is_captured : Cell<bool>, // Set if a function nested in the one owning the frame uses it
+    pub is_defined : Cell<bool>, // Set once a function has been given a body
     pub val_type : Types
 }
 
+// The modifiers which have been applied to a symbol
+#[derive(Clone, Default)]
+pub struct Modifiers {
+    pub is_const : Option<Span>, // Where the symbol was made const
+    pub is_static : bool,
+    pub is_volatile : bool,
+    pub is_pub : bool, // Visible to modules which import the module declaring the symbol
+}
+
 // A wrapper around a hashmap with a frame_id 
 pub struct Scope {
     pub map : HashMap<String, SymbolPtr>,
+    pub structs : HashMap<String, Rc<StructLayout>>, // Structs have their own namespace
+    pub enums : HashMap<String, Rc<EnumLayout>>, // As do enums
+    pub types : HashMap<String, Types>, // Type aliases
     pub frame_id : usize,
 }
 
@@ -92,6 +503,9 @@ impl Scope {
     pub fn new(fid : usize) -> Self {
         Scope {
             map : HashMap::new(),
+            structs : HashMap::new(),
+            enums : HashMap::new(),
+            types : HashMap::new(),
             frame_id : fid,
         }
     }
@@ -107,7 +521,7 @@ pub struct SymbolTable {
     frame_curr : usize,
     frames : usize,
     size : usize, // Total number of variables defined
-
+    outer_modules : Vec<Vec<Scope>>, // Scopes of the modules which are waiting on the current one
 }
 
 impl SymbolTable {
@@ -119,13 +533,41 @@ impl SymbolTable {
             frame_curr : 0,
             frames : 1, // Counter of unique frames encountered so far. Used for fid
             size : 0,
+            outer_modules : Vec::new(),
         }
     }
 
+    #[allow(dead_code)]
     pub fn len(& self) -> usize {
         self.size
     }
+
+    // Return the id of the current stack frame. 0 if global frame
+    pub fn frame(&self) -> usize {
+        self.frame_curr
+    }
     
+    // Start a new module, with its own global scope. The globals of other modules are hidden until
+    // the module is popped, but stay in the global frame
+    pub fn push_module(&mut self) {
+        if self.frame_curr != 0 {panic!("Trying to push a module inside of a function")};
+        let outer = std::mem::replace(&mut self.mapping, Vec::from([Scope::new(0)]));
+        self.outer_modules.push(outer);
+    }
+
+    // Return the global scope of the current module, and return to the module that was current
+    // before it was pushed
+    pub fn pop_module(&mut self) -> Scope {
+        let outer = self.outer_modules.pop().expect("Trying to pop the root module (Mismatched push_module and pop_module)");
+        let module = std::mem::replace(&mut self.mapping, outer);
+        module.into_iter().next().expect("No scope (left global scope)")
+    }
+
+    // Check if the current scope is the global scope of the current module
+    pub fn is_global(&self) -> bool {
+        self.mapping.len() == 1
+    }
+
     // Create a new frame and return its static frameid.
     pub fn push_frame(&mut self) -> usize {
         self.frame_curr = self.frames;
@@ -177,8 +619,16 @@ impl SymbolTable {
         Some(new_ptr)
     }
 
+    // Make an existing symbol, such as one imported from another module, visible in the current
+    // scope under the given name. It is not a new variable, so takes up no more space
+    pub fn insert_symbol(&mut self, name : &str, symbol : SymbolPtr) {
+        self.mapping.last_mut()
+            .expect("No scope (left global scope)")
+            .map.insert(name.to_string(), symbol);
+    }
+
     // Insert into the current scope if it exists
-    pub fn insert(&mut self, name : &str, val_type : &Types, is_const : bool) {
+    pub fn insert(&mut self, name : &str, val_type : &Types, modifiers : Modifiers) {
         self.mapping.last_mut()
             .expect("No scope (left global scope)")
             .map.insert(name.to_string(), SymbolPtr::new(Symbol { 
@@ -186,12 +636,85 @@ impl SymbolTable {
                 // The id of the function that owns the stack frame this var belongs to. 0 if global frame
                 frame_id : self.frame_curr, 
                 offset : self.size - self.static_frame_ptr.last().expect("No frame (left global frame)"), // offset from current static frame pointer.
-                is_const : is_const,
-                is_captured : false,
+                modifiers : RefCell::new(modifiers),
+                is_captured : Cell::new(false),
+                is_defined : Cell::new(false),
                 val_type : val_type.clone()
             }));
         // Increase size afterwards
         self.size += 1;
     }
 
+
+    // Check if a struct name corresponds to a struct defined in the current scope
+    pub fn is_local_struct(&self, name : &str) -> bool {
+        self.mapping
+            .last()
+            .expect("No scope (left global scope)")
+            .structs.contains_key(name)
+    }
+
+    // Return the struct currently mapped to the name (If it exists)
+    pub fn get_struct(&self, name : &str) -> Option<Rc<StructLayout>> {
+        self.mapping
+            .iter()
+            .rev()
+            .find_map(|scope| scope.structs.get(name).cloned())
+    }
+
+    // Insert a struct into the current scope
+    pub fn insert_struct(&mut self, layout : Rc<StructLayout>) {
+        self.mapping.last_mut()
+            .expect("No scope (left global scope)")
+            .structs.insert(layout.name.clone(), layout);
+    }
+
+
+    // Check if an enum name corresponds to an enum defined in the current scope
+    pub fn is_local_enum(&self, name : &str) -> bool {
+        self.mapping
+            .last()
+            .expect("No scope (left global scope)")
+            .enums.contains_key(name)
+    }
+
+    // Return the enum currently mapped to the name (If it exists)
+    pub fn get_enum(&self, name : &str) -> Option<Rc<EnumLayout>> {
+        self.mapping
+            .iter()
+            .rev()
+            .find_map(|scope| scope.enums.get(name).cloned())
+    }
+
+    // Insert an enum into the current scope
+    pub fn insert_enum(&mut self, layout : Rc<EnumLayout>) {
+        self.mapping.last_mut()
+            .expect("No scope (left global scope)")
+            .enums.insert(layout.name.clone(), layout);
+    }
+
+
+    // Check if a type alias is declared in the current scope
+    pub fn is_local_type(&self, name : &str) -> bool {
+        self.mapping
+            .last()
+            .expect("No scope (left global scope)")
+            .types.contains_key(name)
+    }
+
+    // Return the type currently aliased by the name (If it exists)
+    pub fn get_type(&self, name : &str) -> Option<Types> {
+        self.mapping
+            .iter()
+            .rev()
+            .find_map(|scope| scope.types.get(name).cloned())
+    }
+
+    // Insert a type alias into the current scope
+    pub fn insert_type(&mut self, name : &str, aliased : &Types) {
+        self.mapping.last_mut()
+            .expect("No scope (left global scope)")
+            .types.insert(name.to_string(), aliased.clone());
+    }
+
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::scanner::{span::Span, token::Token};
+
+// The syntax errors the parser can recover from. None in place of a token means the end of the
+// input was reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Expected { expected : Token, found : Option<Token> },
+    Unclosed { close : Token, open : Span }, // No closing bracket for the opening one at open
+    ExpectedExpression(Option<Token>),
+    UnexpectedToken(Token), // A token following a complete expression which can't continue it
+    SemiInBrackets,
+    InvalidFunctName, // A function declaration whose name is not an identifier
+    InvalidParam, // A function parameter which is not a declaration
+    ExpectedName(Option<Token>), // A name was expected, such as after struct or .
+    InvalidField, // A struct field which is not a declaration
+    ExpectedTypeName(Option<Token>), // A type was expected, such as in the fields of an enum variant
+    ExpectedImportPath(Option<Token>), // An import not followed by a string or name
+    InvalidTypedef, // A typedef which is not applied to a declaration
+    ExpectedCase(Option<Token>), // A switch arm which doesn't start with case or default
+}
+
+// A syntax error. The parser inserts a Node::Invalid in place of the malformed expression and
+// records one of these, then continues from the next statement boundary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind : ParseErrorKind,
+    pub span : Span,
+}
+
+fn fmt_found(found : &Option<Token>) -> String {
+    match found {
+        Some(t) => format!("{t:?}"),
+        None => "end of file".to_string(),
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Expected { expected, found } => write!(f, "expected {expected:?}, found {}", fmt_found(found)),
+            ParseErrorKind::Unclosed { close, open } => write!(f, "expected {close:?} to close the bracket opened at {open}"),
+            ParseErrorKind::ExpectedExpression(found) => write!(f, "expected an expression, found {}", fmt_found(found)),
+            ParseErrorKind::UnexpectedToken(t) => write!(f, "unexpected {t:?} after expression"),
+            ParseErrorKind::SemiInBrackets => write!(f, "semicolon inside of brackets"),
+            ParseErrorKind::InvalidFunctName => write!(f, "function name must be an identifier"),
+            ParseErrorKind::ExpectedName(found) => write!(f, "expected a name, found {}", fmt_found(found)),
+            ParseErrorKind::InvalidField => write!(f, "struct field must be a declaration, such as int x"),
+            ParseErrorKind::ExpectedTypeName(found) => write!(f, "expected a type, found {}", fmt_found(found)),
+            ParseErrorKind::ExpectedImportPath(found) => write!(f, "expected a path or module name to import, found {}", fmt_found(found)),
+            ParseErrorKind::InvalidTypedef => write!(f, "typedef must be applied to a declaration, such as int *intptr"),
+            ParseErrorKind::ExpectedCase(found) => write!(f, "expected case or default, found {}", fmt_found(found)),
+            ParseErrorKind::InvalidParam => write!(f, "function parameter must be a declaration, such as int a"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
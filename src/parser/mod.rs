@@ -1,11 +1,19 @@
 use std::{collections::HashSet, io::Read};
-use crate::scanner::{token::Token, Scanner};
+use crate::scanner::{span::Span, token::Token, Scanner};
 use crate::ast::{self, Node};
+use error::{ParseError, ParseErrorKind};
 
+pub mod error;
 
 pub struct Parser {
     eof_read : bool,
     open_bracks : u32,
+    labels : Vec<String>, // Labels of the loops enclosing the current position
+    // Names declared as type aliases, in each block enclosing the current position. A type name
+    // can begin a declaration, so has to be told apart from other names while parsing
+    type_names : Vec<HashSet<String>>,
+    exported_types : Vec<String>, // Type aliases declared pub at the top level
+    errors : Vec<ParseError>,
 }
 
 impl Parser {
@@ -13,59 +21,644 @@ impl Parser {
         Self {
             eof_read : false,
             open_bracks : 0,
+            labels : Vec::new(),
+            type_names : Vec::from([HashSet::new()]),
+            exported_types : Vec::new(),
+            errors : Vec::new(),
         }
     }
 
-    pub fn gen_ast<T : Read>(&mut self, tokens : &mut Scanner<T>) -> Node {
-        return self.parse(tokens, 0, &HashSet::new());
+    // Make type aliases declared elsewhere, such as in an imported module, usable as type names
+    pub fn import_type_names(&mut self, names : &[String]) {
+        self.type_names[0].extend(names.iter().cloned());
+    }
+
+    // Return the names of the type aliases which are visible to modules importing this one
+    pub fn exported_type_names(&self) -> &[String] {
+        &self.exported_types
+    }
+
+    fn is_type_name(&self, name : &str) -> bool {
+        self.type_names.iter().any(|scope| scope.contains(name))
+    }
+
+    fn declare_type_name(&mut self, name : &str) {
+        self.type_names.last_mut().expect("No type name scope").insert(name.to_string());
+    }
+
+    // Parse the whole token stream. Syntax errors are replaced by Node::Invalid in the ast, and
+    // returned in the order they were found
+    pub fn gen_ast<T : Read>(&mut self, tokens : &mut Scanner<T>) -> (Node, Vec<ParseError>) {
+        let ast = self.parse(tokens, 0, &HashSet::new());
+        (ast, std::mem::take(&mut self.errors))
+    }
+
+    fn error(&mut self, kind : ParseErrorKind, span : &Span) {
+        self.errors.push(ParseError { kind : kind, span : span.clone() });
+    }
+
+    // Consume the next token if it is the expected one. Otherwise, record an error and leave the
+    // token for the caller to recover from
+    fn expect<T : Read>(&mut self, tok_it : &mut Scanner<T>, expected : Token) -> bool {
+        let found = tok_it.peek();
+        if found.as_ref() == Some(&expected) {
+            tok_it.next();
+            return true;
+        }
+        let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+        self.error(ParseErrorKind::Expected { expected : expected, found : found }, &span);
+        false
+    }
+
+    // Consume the closing bracket matching the opening bracket at open. Otherwise, record an error
+    // and leave the token for the caller to recover from
+    fn expect_close<T : Read>(&mut self, tok_it : &mut Scanner<T>, close : Token, open : &Span) {
+        if tok_it.peek().as_ref() == Some(&close) {
+            tok_it.next();
+            return;
+        }
+        let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+        self.error(ParseErrorKind::Unclosed { close : close, open : open.clone() }, &span);
+    }
+
+    // Skip tokens until the end of the current statement, so that parsing can resume after a
+    // syntax error. Stops before a semicolon, closing brace or match_tok that is not nested within
+    // brackets opened along the way
+    fn synchronize<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>) {
+        let mut depth = 0;
+        while let Some(t) = tok_it.peek() {
+            if depth == 0 && (matches!(t, Token::Semi | Token::RCurly) || match_tok.contains(&t)) {
+                return;
+            }
+            match t {
+                Token::LCurly | Token::LParen | Token::LBrack => depth += 1,
+                Token::RCurly | Token::RParen | Token::RBrack => depth = u32::saturating_sub(depth, 1),
+                _ => (),
+            }
+            tok_it.next();
+        }
     }
 
     // Return the ast representing a parenthesised expression. The open parenthesis should have
     // already been consumed
     fn parse_paren<T : Read>(&mut self, tok_it : &mut Scanner<T>) ->Node {
         // Parse the inside of the paren
-        let lnum = tok_it.lnum;
+        let open = tok_it.span.clone();
         let l = self.parse(tok_it, 2, &HashSet::from([Token::RParen]));
         // Consume the close bracket
-        match tok_it.next() {
-            Some(Token::RParen) => l,
-            Some(x) => panic!("No closing parenthesis for open parenthesis on line {lnum} : Encountered {x:?}"),
-            None => panic!("No closing parenthesis for open parenthesis on line {lnum} : EOF"),
-        }
+        self.expect_close(tok_it, Token::RParen, &open);
+        l
+    }
+
+    // Parse an expression which name resolution gives its own scope, such as a branch of an if, so
+    // that the type names it declares aren't visible after it
+    fn parse_scoped<T : Read>(&mut self, tok_it : &mut Scanner<T>, min_bp : u32, match_tok : &HashSet<Token>) -> Node {
+        self.type_names.push(HashSet::new());
+        let expr = self.parse(tok_it, min_bp, match_tok);
+        self.type_names.pop();
+        expr
     }
 
     // Return the ast representing a scoped expression. The open curly bracket should have already
     // been consumed
     fn parse_scope<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Node {
-        let lnum = tok_it.lnum;
+        let open = tok_it.span.clone();
+        self.type_names.push(HashSet::new());
         let expr = self.parse(tok_it, 0, &HashSet::from([Token::RCurly]));
-        let Some(Token::RCurly) = tok_it.next() else {
-            panic!("No closing brace for open curly braces on line {lnum}")
-        };
-        Node::Block(Box::new(ast::Block {
-            lnum : lnum,
-            statements : expr,
-            scope : None
-        }))
+        self.type_names.pop();
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_block(&open.to(&tok_it.span), expr, None)
     }
 
     // Return the ast representing a parenthesised expression. The open parenthesis should have
     // already been consumed
     fn parse_array<T : Read>(&mut self, tok_it : &mut Scanner<T>) ->Node {
-        let lnum = tok_it.lnum;
+        let open = tok_it.span.clone();
         let mt = HashSet::from([Token::RBrack, Token::Comma]);
         let mut elements = Vec::new();
         let mut e = self.parse(tok_it, 2, &mt);
         while let Some(Token::Comma) = tok_it.peek() {
             tok_it.next();
             elements.push(e);
-            e = self.parse(tok_it, 0, &mt);
+            e = self.parse(tok_it, 2, &mt);
         }
-        let Some(Token::RBrack) = tok_it.next() else {
-            panic!("Array on line {lnum} not closed")
-        };
+        self.expect_close(tok_it, Token::RBrack, &open);
         elements.push(e);
-        Node::new_array(&lnum, elements)
+        Node::new_array(&open.to(&tok_it.span), elements)
+    }
+
+    // Return the ast representing a while loop. The while keyword, and label if the loop has one,
+    // should have already been consumed. start is the span of the first of these
+    fn parse_while<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>, label : Option<String>, start : &Span) -> Node {
+        if !self.expect(tok_it, Token::LParen) {
+            self.synchronize(tok_it, match_tok);
+            return Node::new_invalid(&start.to(&tok_it.span))
+        }
+        let condition = self.parse_paren(tok_it);
+        let body = self.parse_loop_body(tok_it, match_tok, &label);
+        Node::new_while(&start.to(&tok_it.span), label, condition, body)
+    }
+
+    // Return the ast representing a for loop. The for keyword, and label if the loop has one,
+    // should have already been consumed. start is the span of the first of these
+    fn parse_for<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>, label : Option<String>, start : &Span) -> Node {
+        // Anything declared in the header is scoped to the loop
+        self.type_names.push(HashSet::new());
+        let for_loop = self.parse_for_clauses(tok_it, match_tok, label, start);
+        self.type_names.pop();
+        for_loop
+    }
+
+    fn parse_for_clauses<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>, label : Option<String>, start : &Span) -> Node {
+        // The semicolons in the header separate its clauses, rather than joining
+        // statements, so each clause is parsed up to the next one
+        let semi = HashSet::from([Token::Semi, Token::RParen]);
+        if !self.expect(tok_it, Token::LParen) {
+            self.synchronize(tok_it, match_tok);
+            return Node::new_invalid(&start.to(&tok_it.span))
+        }
+        let init = self.parse(tok_it, 2, &semi);
+        if !self.expect(tok_it, Token::Semi) {
+            self.synchronize(tok_it, match_tok);
+            return Node::new_invalid(&start.to(&tok_it.span))
+        }
+        let condition = self.parse(tok_it, 2, &semi);
+        if !self.expect(tok_it, Token::Semi) {
+            self.synchronize(tok_it, match_tok);
+            return Node::new_invalid(&start.to(&tok_it.span))
+        }
+        let step = self.parse_paren(tok_it);
+        let body = self.parse_loop_body(tok_it, match_tok, &label);
+        Node::new_for(&start.to(&tok_it.span), label, init, condition, step, body)
+    }
+
+    // Parse the body of a loop, with its label (if any) visible to break and continue
+    fn parse_loop_body<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>, label : &Option<String>) -> Node {
+        if let Some(l) = label {
+            self.labels.push(l.clone());
+        }
+        let body = self.parse_scoped(tok_it, 2, match_tok);
+        if label.is_some() {
+            self.labels.pop();
+        }
+        body
+    }
+
+    // Consume the label after a break or continue, if there is one. Labels take precedence over
+    // variables of the same name, so break x where x labels an enclosing loop never breaks with
+    // the value of a variable x
+    fn parse_jump_label<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Option<String> {
+        match tok_it.peek() {
+            Some(Token::Id(s)) if self.labels.contains(&s) => {
+                tok_it.next();
+                Some(s)
+            },
+            _ => None,
+        }
+    }
+
+    // Return the ast of a prefix operator applied to right. A type specifier applied to a call
+    // declares a function instead
+    fn prefix_node(&mut self, op : Token, op_span : &Span, right : Node) -> Node {
+        let span = cover(op_span, &Node::Empty, &right);
+        match right {
+            Node::Funct(f) if op.is_type_spec() => self.parse_funct_decl(&span, vec![op], *f),
+            // A function returning a pointer has a Star for each pointer before its name, as in
+            // int *f()
+            Node::PrefixOp(p) if op.is_type_spec() && is_pointer_funct(&p) => {
+                let mut ret_type = vec![op];
+                let mut declarator = Node::PrefixOp(p);
+                while let Node::PrefixOp(p) = declarator {
+                    ret_type.push(Token::Star);
+                    declarator = p.rhs;
+                }
+                let Node::Funct(f) = declarator else {
+                    unreachable!()
+                };
+                self.parse_funct_decl(&span, ret_type, *f)
+            },
+            // Multiple type specifiers, as in unsigned long f(), all apply to the return type
+            Node::FunctDecl(mut f) if op.is_type_spec() && matches!(f.body, Node::Empty) => {
+                add_ret_spec(&mut f.ret_type, op);
+                f.span = span;
+                Node::FunctDecl(f)
+            },
+            Node::Lambda(mut l) if op.is_type_spec() => {
+                add_ret_spec(&mut l.ret_type, op);
+                l.span = span;
+                Node::Lambda(l)
+            },
+            Node::PrefixOp(mut p) if op.is_type_spec() && matches!(p.op_type, Token::FunctType(..)) => {
+                let Token::FunctType(ret_type, _) = &mut p.op_type else {
+                    unreachable!()
+                };
+                add_ret_spec(ret_type, op);
+                p.span = span;
+                Node::PrefixOp(p)
+            },
+            // Exported type aliases are type names in the modules which import them
+            Node::TypeDef(t) if op == Token::Pub && self.type_names.len() == 1 => {
+                self.exported_types.push(t.name.clone());
+                Node::new_prefix(&span, &op, Node::TypeDef(t))
+            },
+            right => Node::new_prefix(&span, &op, right),
+        }
+    }
+
+    // Return the ast representing the declarator following a type specifier. The stars of a
+    // declarator declare pointers rather than dereferencing, so they bind like the type specifier,
+    // and int *a[3] declares an array of pointers rather than a pointer to an element
+    fn parse_declarator<T : Read>(&mut self, tok_it : &mut Scanner<T>, match_tok : &HashSet<Token>) -> Node {
+        if tok_it.peek() != Some(Token::Star) {
+            return self.parse(tok_it, 34, match_tok)
+        }
+        tok_it.next();
+        let star_span = tok_it.span.clone();
+        let right = self.parse_declarator(tok_it, match_tok);
+        Node::new_prefix(&cover(&star_span, &Node::Empty, &right), &Token::Star, right)
+    }
+
+    // Consume a name, such as the name of a struct or field. Otherwise, record an error and leave
+    // the token for the caller to recover from
+    fn parse_name<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Option<String> {
+        match tok_it.peek() {
+            Some(Token::Id(name)) => {
+                tok_it.next();
+                Some(name)
+            },
+            found => {
+                let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+                self.error(ParseErrorKind::ExpectedName(found), &span);
+                None
+            },
+        }
+    }
+
+    // Consume a type name, such as unsigned long or struct P *. Otherwise, record an error and
+    // leave the token for the caller to recover from
+    fn parse_type_name<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Option<ast::TypeName> {
+        let start = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+        let mut specs = Vec::new();
+        loop {
+            match tok_it.peek() {
+                Some(spec) if spec.is_type_spec() => {
+                    tok_it.next();
+                    specs.push(spec);
+                },
+                Some(Token::Id(name)) if self.is_type_name(&name) => {
+                    tok_it.next();
+                    specs.push(Token::TypeName(name));
+                },
+                Some(key @ (Token::StructKey | Token::EnumKey)) => {
+                    tok_it.next();
+                    let name = self.parse_name(tok_it)?;
+                    specs.push(tagged_type(&key, name));
+                },
+                _ => break,
+            }
+        }
+        if specs.is_empty() {
+            let found = tok_it.peek();
+            self.error(ParseErrorKind::ExpectedTypeName(found), &start);
+            return None
+        }
+        let mut pointers = 0;
+        while let Some(Token::Star) = tok_it.peek() {
+            tok_it.next();
+            pointers += 1;
+        }
+        Some(ast::TypeName { span : start.to(&tok_it.span), specs : specs, pointers : pointers })
+    }
+
+    // Check if the tokens starting n tokens ahead are a type name followed by a close parenthesis,
+    // as in a cast or sizeof(TYPE). A parenthesised expression such as (x * y) or (int x = 3) is
+    // not, so this decides how an open parenthesis is parsed
+    fn type_name_ahead<T : Read>(&self, tok_it : &mut Scanner<T>, mut n : usize) -> bool {
+        let start = n;
+        loop {
+            match tok_it.peek_nth(n) {
+                Some(spec) if spec.is_type_spec() => n += 1,
+                Some(Token::Id(name)) if self.is_type_name(&name) => n += 1,
+                Some(Token::StructKey | Token::EnumKey) if matches!(tok_it.peek_nth(n + 1), Some(Token::Id(_))) => n += 2,
+                _ => break,
+            }
+        }
+        while let Some(Token::Star) = tok_it.peek_nth(n) {
+            n += 1;
+        }
+        n > start && tok_it.peek_nth(n) == Some(Token::RParen)
+    }
+
+    // Return the ast representing a struct definition. The open curly bracket should have already
+    // been consumed. start is the span of the struct keyword and name
+    fn parse_struct_decl<T : Read>(&mut self, tok_it : &mut Scanner<T>, name : &str, start : &Span) -> Node {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::Semi, Token::RCurly]);
+        let mut fields = Vec::new();
+        while !matches!(tok_it.peek(), Some(Token::RCurly) | None) {
+            let field = self.parse(tok_it, 2, &mt);
+            if is_declaration(&field) {
+                fields.push(field);
+            } else if let Some(span) = field.span() {
+                self.error(ParseErrorKind::InvalidField, span);
+            }
+            // Each field ends in a semicolon
+            if !self.expect(tok_it, Token::Semi) {
+                self.synchronize(tok_it, &mt);
+                if let Some(Token::Semi) = tok_it.peek() {
+                    tok_it.next();
+                }
+            }
+        }
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_struct_decl(&start.to(&tok_it.span), name, fields)
+    }
+
+    // Return the ast representing a struct literal. The open curly bracket should have already
+    // been consumed. start is the span of the struct name
+    fn parse_struct_lit<T : Read>(&mut self, tok_it : &mut Scanner<T>, name : &str, start : &Span) -> Node {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::Comma, Token::RCurly]);
+        let mut fields = Vec::new();
+        while !matches!(tok_it.peek(), Some(Token::RCurly) | None) {
+            let field_start = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+            let Some(field) = self.parse_name(tok_it) else {
+                self.synchronize(tok_it, &mt);
+                break;
+            };
+            if !self.expect(tok_it, Token::Colon) {
+                self.synchronize(tok_it, &mt);
+                break;
+            }
+            let val = self.parse(tok_it, 2, &mt);
+            fields.push(ast::FieldInit {
+                span : cover(&field_start, &Node::Empty, &val),
+                name : field,
+                val : val,
+            });
+            if let Some(Token::Comma) = tok_it.peek() {
+                tok_it.next();
+            } else {
+                break;
+            }
+        }
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_struct_lit(&start.to(&tok_it.span), name, fields)
+    }
+
+    // Return the ast representing an enum definition. The open curly bracket should have already
+    // been consumed. start is the span of the enum keyword and name
+    fn parse_enum_decl<T : Read>(&mut self, tok_it : &mut Scanner<T>, name : &str, start : &Span) -> Node {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::Comma, Token::RCurly]);
+        let mut variants = Vec::new();
+        while !matches!(tok_it.peek(), Some(Token::RCurly) | None) {
+            let variant_start = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+            let Some(variant) = self.parse_name(tok_it) else {
+                self.synchronize(tok_it, &mt);
+                break;
+            };
+            // The fields of a variant are optional, and are given as a list of types
+            let mut fields = Vec::new();
+            if let Some(Token::LParen) = tok_it.peek() {
+                tok_it.next();
+                let paren = tok_it.span.clone();
+                while !matches!(tok_it.peek(), Some(Token::RParen) | None) {
+                    let Some(field) = self.parse_type_name(tok_it) else {
+                        self.synchronize(tok_it, &HashSet::from([Token::RParen]));
+                        break;
+                    };
+                    fields.push(field);
+                    if let Some(Token::Comma) = tok_it.peek() {
+                        tok_it.next();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_close(tok_it, Token::RParen, &paren);
+            }
+            variants.push(ast::Variant {
+                span : variant_start.to(&tok_it.span),
+                name : variant,
+                fields : fields,
+            });
+            if let Some(Token::Comma) = tok_it.peek() {
+                tok_it.next();
+            } else {
+                break;
+            }
+        }
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_enum_decl(&start.to(&tok_it.span), name, variants)
+    }
+
+    // Return the ast representing the arms of a match on val. The open curly bracket should have
+    // already been consumed. start is the span of the match keyword
+    fn parse_match<T : Read>(&mut self, tok_it : &mut Scanner<T>, val : Node, start : &Span) -> Node {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::Comma, Token::RCurly]);
+        let mut arms = Vec::new();
+        while !matches!(tok_it.peek(), Some(Token::RCurly) | None) {
+            let arm_start = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+            let Some(variant) = self.parse_name(tok_it) else {
+                self.synchronize(tok_it, &mt);
+                break;
+            };
+            let mut bindings = Vec::new();
+            if let Some(Token::LParen) = tok_it.peek() {
+                tok_it.next();
+                let paren = tok_it.span.clone();
+                while !matches!(tok_it.peek(), Some(Token::RParen) | None) {
+                    let Some(binding) = self.parse_name(tok_it) else {
+                        self.synchronize(tok_it, &HashSet::from([Token::RParen]));
+                        break;
+                    };
+                    bindings.push(binding);
+                    if let Some(Token::Comma) = tok_it.peek() {
+                        tok_it.next();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_close(tok_it, Token::RParen, &paren);
+            }
+            if !self.expect(tok_it, Token::FatArrow) {
+                self.synchronize(tok_it, &mt);
+                break;
+            }
+            let body = self.parse_scoped(tok_it, 2, &mt);
+            arms.push(ast::MatchArm {
+                span : cover(&arm_start, &Node::Empty, &body),
+                // _ matches any variant
+                variant : if variant == "_" {None} else {Some(variant)},
+                bindings : bindings,
+                body : body,
+                tag : None,
+            });
+            if let Some(Token::Comma) = tok_it.peek() {
+                tok_it.next();
+            } else {
+                break;
+            }
+        }
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_match(&start.to(&tok_it.span), val, arms)
+    }
+
+    // Return the ast representing a switch on val, given its arms of the form case CONSTS: EXPR; or
+    // default: EXPR;. The open curly bracket should have already been consumed. start is the span
+    // of the switch keyword
+    fn parse_switch<T : Read>(&mut self, tok_it : &mut Scanner<T>, val : Node, start : &Span) -> Node {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::Case, Token::Default, Token::RCurly]);
+        let mut arms = Vec::new();
+        while !matches!(tok_it.peek(), Some(Token::RCurly) | None) {
+            // The semicolon ends the arm before it rather than joining it to the next expression,
+            // so an arm with several statements needs a block
+            if let Some(Token::Semi) = tok_it.peek() {
+                tok_it.next();
+                continue;
+            }
+            let arm_start = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+            let cases = match tok_it.next() {
+                Some(Token::Case) => Some(self.parse_cases(tok_it)),
+                Some(Token::Default) => None,
+                found => {
+                    self.error(ParseErrorKind::ExpectedCase(found), &arm_start);
+                    self.synchronize(tok_it, &mt);
+                    continue;
+                },
+            };
+            if !self.expect(tok_it, Token::Colon) {
+                self.synchronize(tok_it, &mt);
+                continue;
+            }
+            let body = self.parse_scoped(tok_it, 2, &mt);
+            arms.push(ast::SwitchArm {
+                span : cover(&arm_start, &Node::Empty, &body),
+                cases : cases,
+                body : body,
+            });
+        }
+        self.expect_close(tok_it, Token::RCurly, &open);
+        Node::new_switch(&start.to(&tok_it.span), val, arms)
+    }
+
+    // Return the comma separated values of a case. The case keyword should have already been
+    // consumed
+    fn parse_cases<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Vec<Node> {
+        let mut cases = Vec::from([self.parse_case(tok_it)]);
+        while let Some(Token::Comma) = tok_it.peek() {
+            tok_it.next();
+            cases.push(self.parse_case(tok_it));
+        }
+        cases
+    }
+
+    // Return a single value of a case, which can't be left out as in case : or case 1, :
+    fn parse_case<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Node {
+        let mt = HashSet::from([Token::Comma, Token::Colon]);
+        let case = self.parse(tok_it, 2, &mt);
+        if !matches!(case, Node::Empty) {
+            return case
+        }
+        let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+        self.error(ParseErrorKind::ExpectedExpression(tok_it.peek()), &span);
+        Node::new_invalid(&span)
+    }
+
+    // Return the ast representing the declaration of a function returning ret_type, given the call
+    // expression the type specifier was applied to. The body is filled in if the declaration is
+    // later assigned to
+    fn parse_funct_decl(&mut self, span : &Span, ret_type : Vec<Token>, call : ast::Funct) -> Node {
+        let Node::Id(name) = call.name else {
+            self.error(ParseErrorKind::InvalidFunctName, call.name.span().unwrap_or(span));
+            return Node::new_invalid(span)
+        };
+        let params = self.funct_params(call.args, span);
+        Node::new_funct_decl(span, &name.name, params, &ret_type, Node::Empty)
+    }
+
+    // Return the ast representing a lambda returning ret_type. start is the span of the return
+    // type, and the open parenthesis of the parameters should be next. Without a body, the return
+    // type and parameters instead name a function type, as in int(int) f, which may itself be the
+    // return type of a lambda or function type
+    fn parse_lambda<T : Read>(&mut self, tok_it : &mut Scanner<T>, ret_type : Token, start : &Span, match_tok : &HashSet<Token>) -> Node {
+        tok_it.next();
+        let args = self.parse_args(tok_it);
+        if matches!(tok_it.peek(), Some(Token::LParen | Token::Star | Token::Id(_))) {
+            let params = args.iter().filter(|a| !matches!(a, Node::Empty)).map(|a| self.param_specs(a, start)).collect();
+            let funct_type = Token::FunctType(Vec::from([ret_type]), params);
+            if let Some(Token::LParen) = tok_it.peek() {
+                return self.parse_lambda(tok_it, funct_type, start, match_tok)
+            }
+            let right = self.parse_declarator(tok_it, match_tok);
+            return self.prefix_node(funct_type, start, right)
+        }
+        let params = self.funct_params(args, start);
+        if !self.expect(tok_it, Token::FatArrow) {
+            self.synchronize(tok_it, match_tok);
+            return Node::new_invalid(&start.to(&tok_it.span))
+        }
+        // The body extends as far as the right side of an assignment would
+        let body = self.parse_scoped(tok_it, 4, match_tok);
+        Node::new_lambda(&cover(start, &Node::Empty, &body), params, &[ret_type], body)
+    }
+
+    // Return the comma separated arguments of a call, or parameters of a lambda. The open
+    // parenthesis should have already been consumed
+    fn parse_args<T : Read>(&mut self, tok_it : &mut Scanner<T>) -> Vec<Node> {
+        let open = tok_it.span.clone();
+        let mt = HashSet::from([Token::RParen, Token::Comma]);
+        let mut args = Vec::new();
+        let mut ai = self.parse(tok_it, 2, &mt);
+        while let Some(Token::Comma) = tok_it.peek() {
+            tok_it.next();
+            args.push(ai);
+            ai = self.parse(tok_it, 2, &mt);
+        }
+        self.expect_close(tok_it, Token::RParen, &open);
+        args.push(ai);
+        args
+    }
+
+    // Return the type specifiers of a parameter of a function type, followed by a Star for each
+    // pointer. The parameter may be named, as in int(int c), but the name is ignored
+    fn param_specs(&mut self, arg : &Node, span : &Span) -> Vec<Token> {
+        let mut specs = Vec::new();
+        let mut curr = arg;
+        while let Node::PrefixOp(op) = curr {
+            if !op.op_type.is_type_spec() && (op.op_type != Token::Star || specs.is_empty()) {
+                break;
+            }
+            specs.push(op.op_type.clone());
+            curr = &op.rhs;
+        }
+        if specs.is_empty() || !matches!(curr, Node::Empty | Node::Id(_)) {
+            self.error(ParseErrorKind::InvalidParam, arg.span().unwrap_or(span));
+        }
+        specs
+    }
+
+    // Return the parameters declared by the arguments of a function declaration or lambda
+    fn funct_params(&mut self, args : Vec<Node>, span : &Span) -> Vec<ast::Param> {
+        let mut params = Vec::new();
+        for arg in args {
+            // int f() has no parameters, rather than a single empty one
+            if let Node::Empty = arg {
+                continue;
+            }
+            let arg_span = arg.span().unwrap_or(span).clone();
+            match declaration_name(&arg) {
+                Some(param_name) => params.push(ast::Param {
+                    span : arg_span,
+                    name : param_name.to_string(),
+                    decl : arg,
+                }),
+                None => self.error(ParseErrorKind::InvalidParam, &arg_span),
+            }
+        }
+        params
     }
 
     // This parser uses pratt parsing, which works somewhat similarly to recursive descent. It will
@@ -82,15 +675,41 @@ impl Parser {
         }
         let Some(x) = tok_it.peek() else {
             self.eof_read = true;
+            // The program may end after a statement, but not in the middle of an expression
+            if min_bp > 0 {
+                self.error(ParseErrorKind::ExpectedExpression(None), &tok_it.span);
+                return Node::new_invalid(&tok_it.span)
+            }
             return Node::Empty
         };
         if match_tok.contains(&x) {
             return Node::Empty
         }
+        if closes_expression(&x) {
+            // An empty statement is fine, and the semicolon will be parsed as an operator below
+            if x == Token::Semi && min_bp == 0 {
+                return self.parse_operators(tok_it, Node::Empty, min_bp, match_tok);
+            }
+            let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+            self.error(ParseErrorKind::ExpectedExpression(Some(x.clone())), &span);
+            // A stray closing brace has nothing to match, so discard it here rather than leaving
+            // it to end the current statement
+            if x == Token::RCurly {
+                tok_it.next();
+            } else {
+                self.synchronize(tok_it, match_tok);
+            }
+            return self.parse_operators(tok_it, Node::new_invalid(&span), min_bp, match_tok);
+        }
         // Don't advance if we encountered a match_tok: Instead, return until the parse which
         // started the match can handle it
         tok_it.next();
-        let mut left = match x {
+        // Names of type aliases are type specifiers
+        let x = match x {
+            Token::Id(name) if self.is_type_name(&name) => Token::TypeName(name),
+            x => x,
+        };
+        let left = match x {
             // Program-level patterns
             // Encounter the counterpart to an open Token pair
             Token::EOF => {
@@ -98,50 +717,260 @@ impl Parser {
                 return Node::Empty
             },
             // Primary Expressions
-            Token::IntConst(i) => Node::new_int(&tok_it.lnum, &i), // Int constant
-            Token::CharConst(c) => Node::new_char(&tok_it.lnum, &c), // Char constant
-            Token::StrConst(s) => Node::new_str(&tok_it.lnum, &s), // String constant
-            Token::Id(s) => Node::new_id(&tok_it.lnum, &s),
+            Token::IntConst(i, suffix, radix) => Node::new_int(&tok_it.span, &i, &suffix, radix), // Int constant
+            Token::FloatConst(f, suffix) => Node::new_float(&tok_it.span, &f, &suffix), // Float constant
+            Token::BoolConst(b) => Node::new_bool(&tok_it.span, &b), // Bool constant
+            Token::CharConst(c) => Node::new_char(&tok_it.span, &c), // Char constant
+            Token::StrConst(s) => Node::new_str(&tok_it.span, &s), // String constant
+            // A name followed by a token being matched, such as the colon after a case, is just a name
+            Token::Id(s) => match (tok_it.peek(), tok_it.peek_nth(1)) {
+                // Struct literal
+                (Some(Token::LCurly), _) if !match_tok.contains(&Token::LCurly) => {
+                    let name_span = tok_it.span.clone();
+                    tok_it.next();
+                    self.parse_struct_lit(tok_it, &s, &name_span)
+                },
+                // Labelled loop
+                (Some(Token::Colon), Some(Token::While | Token::For)) if !match_tok.contains(&Token::Colon) => {
+                    let label_span = tok_it.span.clone();
+                    tok_it.next();
+                    if let Some(Token::While) = tok_it.next() {
+                        self.parse_while(tok_it, match_tok, Some(s), &label_span)
+                    } else {
+                        self.parse_for(tok_it, match_tok, Some(s), &label_span)
+                    }
+                },
+                _ => Node::new_id(&tok_it.span, &s),
+            },
+            // The scanner has already recorded an error for invalid tokens, so parse around them
+            Token::INVAL => Node::new_invalid(&tok_it.span),
             Token::LCurly => self.parse_scope(tok_it),
+            // Casts, which convert the expression following a parenthesised type name
+            Token::LParen if self.type_name_ahead(tok_it, 0) => {
+                let open = tok_it.span.clone();
+                let Some(type_name) = self.parse_type_name(tok_it) else {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&open), min_bp, match_tok);
+                };
+                self.expect_close(tok_it, Token::RParen, &open);
+                // Binds like the other prefix operators, so (int) x++ converts the result of x++
+                let expr = self.parse(tok_it, 28, match_tok);
+                Node::new_cast(&cover(&open, &Node::Empty, &expr), type_name, expr)
+            },
             // Parenthesis expressions
             Token::LParen => self.parse_paren(tok_it),
             // Constant Array expressions
             Token::LBrack => self.parse_array(tok_it), 
             Token::If => { // If expressions
-                let lnum = tok_it.lnum;
-                let Some(Token::LParen) = tok_it.next() else {
-                    panic!("Expected parenthesis after if on line {lnum}")
-                };
+                let if_span = tok_it.span.clone();
+                if !self.expect(tok_it, Token::LParen) {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&if_span.to(&tok_it.span)), min_bp, match_tok);
+                }
                 // Parse the expression on the inside of the paren
                 let condition = self.parse_paren(tok_it);
                 // Consume the close bracket
                 let mut tmatch_tok = match_tok.clone(); // Not too bad since this is only ever 2-3
                 tmatch_tok.insert(Token::Else); // Doesn't matter to us if Else is already being
                                                 // matched
-                let tbranch = self.parse(tok_it, 2, &tmatch_tok);
+                let tbranch = self.parse_scoped(tok_it, 2, &tmatch_tok);
                 let fbranch = if let Some(Token::Else) = tok_it.peek() {
                     tok_it.next();
-                    self.parse(tok_it, 2, match_tok)
+                    self.parse_scoped(tok_it, 2, match_tok)
                 } else {
                     Node::Empty
                 };
-                Node::new_if(&lnum, condition, tbranch, fbranch)
+                let end = tok_it.span.clone();
+                Node::new_if(&if_span.to(&end), condition, tbranch, fbranch)
+            },
+            Token::While => { // While expressions
+                let while_span = tok_it.span.clone();
+                self.parse_while(tok_it, match_tok, None, &while_span)
+            },
+            Token::For => { // For expressions
+                let for_span = tok_it.span.clone();
+                self.parse_for(tok_it, match_tok, None, &for_span)
+            },
+            Token::Break => { // Break expressions, with an optional label and value
+                let break_span = tok_it.span.clone();
+                let label = self.parse_jump_label(tok_it);
+                let start = break_span.to(&tok_it.span);
+                let val = match tok_it.peek() {
+                    Some(t) if !closes_expression(&t) && !match_tok.contains(&t) => self.parse(tok_it, 4, match_tok),
+                    _ => Node::Empty,
+                };
+                Node::new_break(&cover(&start, &Node::Empty, &val), label, val)
+            },
+            key @ (Token::StructKey | Token::EnumKey) => { // Struct and enum definitions, or their types in declarations
+                let key_span = tok_it.span.clone();
+                let Some(name) = self.parse_name(tok_it) else {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&key_span), min_bp, match_tok);
+                };
+                let start = key_span.to(&tok_it.span);
+                match (key, tok_it.peek()) {
+                    (Token::StructKey, Some(Token::LCurly)) => {
+                        tok_it.next();
+                        self.parse_struct_decl(tok_it, &name, &start)
+                    },
+                    (Token::EnumKey, Some(Token::LCurly)) => {
+                        tok_it.next();
+                        self.parse_enum_decl(tok_it, &name, &start)
+                    },
+                    (key, Some(Token::LParen)) => self.parse_lambda(tok_it, tagged_type(&key, name), &start, match_tok),
+                    (key, _) => {
+                        let right = self.parse_declarator(tok_it, match_tok);
+                        self.prefix_node(tagged_type(&key, name), &start, right)
+                    },
+                }
+            },
+            Token::Import => { // Imports of other modules, by path or by name
+                let import_span = tok_it.span.clone();
+                match tok_it.peek() {
+                    Some(Token::StrConst(path) | Token::Id(path)) => {
+                        tok_it.next();
+                        Node::new_import(&import_span.to(&tok_it.span), &path)
+                    },
+                    found => {
+                        let span = tok_it.peek_span().unwrap_or(tok_it.span.clone());
+                        self.error(ParseErrorKind::ExpectedImportPath(found), &span);
+                        self.synchronize(tok_it, match_tok);
+                        Node::new_invalid(&import_span)
+                    },
+                }
+            },
+            Token::Typedef => { // Type aliases named by a declaration
+                let typedef_span = tok_it.span.clone();
+                // Binds loosely enough to include array declarators, as in typedef int triple[3]
+                let decl = self.parse(tok_it, 30, match_tok);
+                let span = cover(&typedef_span, &Node::Empty, &decl);
+                match declaration_name(&decl).map(|name| name.to_string()) {
+                    Some(name) => {
+                        self.declare_type_name(&name);
+                        Node::new_typedef(&span, &name, ast::Aliased::Decl(decl))
+                    },
+                    None => {
+                        self.error(ParseErrorKind::InvalidTypedef, &span);
+                        Node::new_invalid(&span)
+                    },
+                }
+            },
+            Token::TypeKey => { // Type aliases of the form type NAME = TYPE
+                let type_span = tok_it.span.clone();
+                let Some(name) = self.parse_name(tok_it) else {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&type_span), min_bp, match_tok);
+                };
+                let type_name = match self.expect(tok_it, Token::Assign) {
+                    true => self.parse_type_name(tok_it),
+                    false => None,
+                };
+                let Some(type_name) = type_name else {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&type_span.to(&tok_it.span)), min_bp, match_tok);
+                };
+                // The alias is declared after the type it aliases, so type T = T refers to an
+                // outer T
+                self.declare_type_name(&name);
+                Node::new_typedef(&type_span.to(&tok_it.span), &name, ast::Aliased::TypeName(type_name))
+            },
+            Token::Sizeof => { // Sizes of types, or of the types of expressions
+                let sizeof_span = tok_it.span.clone();
+                if tok_it.peek() != Some(Token::LParen) || !self.type_name_ahead(tok_it, 1) {
+                    let expr = self.parse_scoped(tok_it, 28, match_tok);
+                    let span = cover(&sizeof_span, &Node::Empty, &expr);
+                    return self.parse_operators(tok_it, Node::new_sizeof(&span, ast::SizeofArg::Expr(expr)), min_bp, match_tok);
+                }
+                tok_it.next();
+                let open = tok_it.span.clone();
+                let Some(type_name) = self.parse_type_name(tok_it) else {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&sizeof_span.to(&tok_it.span)), min_bp, match_tok);
+                };
+                self.expect_close(tok_it, Token::RParen, &open);
+                Node::new_sizeof(&sizeof_span.to(&tok_it.span), ast::SizeofArg::TypeName(type_name))
+            },
+            Token::Match => { // Match expressions
+                let match_span = tok_it.span.clone();
+                if !self.expect(tok_it, Token::LParen) {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&match_span.to(&tok_it.span)), min_bp, match_tok);
+                }
+                let val = self.parse_paren(tok_it);
+                if !self.expect(tok_it, Token::LCurly) {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&match_span.to(&tok_it.span)), min_bp, match_tok);
+                }
+                self.parse_match(tok_it, val, &match_span)
+            },
+            Token::Switch => { // Switch expressions
+                let switch_span = tok_it.span.clone();
+                if !self.expect(tok_it, Token::LParen) {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&switch_span.to(&tok_it.span)), min_bp, match_tok);
+                }
+                let val = self.parse_paren(tok_it);
+                if !self.expect(tok_it, Token::LCurly) {
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&switch_span.to(&tok_it.span)), min_bp, match_tok);
+                }
+                self.parse_switch(tok_it, val, &switch_span)
+            },
+            Token::Continue => { // Continue expressions, with an optional label
+                let continue_span = tok_it.span.clone();
+                let label = self.parse_jump_label(tok_it);
+                Node::new_continue(&continue_span.to(&tok_it.span), label)
+            },
+            // A type specifier followed by parameters starts a lambda
+            op if op.is_type_spec() && tok_it.peek() == Some(Token::LParen) => {
+                let op_span = tok_it.span.clone();
+                self.parse_lambda(tok_it, op, &op_span, match_tok)
             },
             // Prefix expressions. 
             op => {
+                let op_span = tok_it.span.clone();
                 let Some(((), rbp)) = self.get_prefix_bp(&op, match_tok) else {
-                    panic!("Error, bad prefix operator")
+                    self.error(ParseErrorKind::ExpectedExpression(Some(op)), &op_span);
+                    self.synchronize(tok_it, match_tok);
+                    return self.parse_operators(tok_it, Node::new_invalid(&op_span), min_bp, match_tok);
                 };
-                let right = self.parse(tok_it, rbp, match_tok);
-                Node::new_prefix(&tok_it.lnum, &op, right)
+                // Return is allowed without a value
+                let right = match tok_it.peek() {
+                    Some(t) if op == Token::Ret && (closes_expression(&t) || match_tok.contains(&t)) => Node::Empty,
+                    None if op == Token::Ret => Node::Empty,
+                    _ if op.is_type_spec() => self.parse_declarator(tok_it, match_tok),
+                    _ => self.parse(tok_it, rbp, match_tok),
+                };
+                self.prefix_node(op, &op_span, right)
             },
         };
+        self.parse_operators(tok_it, left, min_bp, match_tok)
+    }
+
+    // The second half of parse. Given the ast of the expression up to the next operator, extend it
+    // with the operators which follow, until an operator which binds less tightly than min_bp is
+    // reached
+    fn parse_operators<T : Read>(&mut self, tok_it : &mut Scanner<T>, mut left : Node, min_bp : u32, match_tok : &HashSet<Token>) -> Node {
         // Each iteration, the iterator is positioned at an operator which 
         // will join the current left sub tree with a new, recursively calculated right subtree. 
         // We also advance the iterator past the right subtree, and set the tree with this operator as root.
         // let mut lookahead = tok_it.clone();
         while let Some(op) = tok_it.peek() {
-            let lnum = tok_it.lnum;
+            let Some(op_span) = tok_it.peek_span() else {break};
+            if let Token::INVAL = op {
+                tok_it.next();
+                continue;
+            }
+            // As in C, an expression ending in a closed brace followed by the start of another
+            // expression is a complete statement, even without a semicolon
+            if ends_with_block(&left) && starts_expression(&op) {
+                if 1 < min_bp {
+                    return left;
+                }
+                let right = self.parse(tok_it, 0, match_tok);
+                left = Node::new_statement(&cover(&op_span, &left, &right), left, right);
+                continue;
+            }
             // First check if it is a postfix operator
             if let Some((lbp, ())) = self.get_postfix_bp(&op, match_tok) {
                 // The subtree to the left of this is more strongly attracted to the previous operator
@@ -156,58 +985,97 @@ impl Parser {
                         let mut mt = HashSet::new();
                         mt.insert(Token::RBrack);
                         let ind = self.parse(tok_it, 0, &mt);
-                        let Some(Token::RBrack) = tok_it.next() else {
-                            panic!("Unmatched open bracket")
-                        };
+                        self.expect_close(tok_it, Token::RBrack, &op_span);
                         self.open_bracks -= 1;
-                        Node::new_infix(&lnum, &op, left, ind)
+                        let span = cover(&tok_it.span, &left, &Node::Empty);
+                        // Brackets after a declaration declare an array, rather than indexing
+                        if is_declaration(&left) {
+                            array_decl(&span, left, ind)
+                        } else {
+                            Node::new_infix(&span, &op, left, ind)
+                        }
                     },
                     // Function call
                     Token::LParen => {
-                        let mut mt = HashSet::new();
-                        mt.insert(Token::RParen);
-                        mt.insert(Token::Comma);
-                        let mut args = Vec::new();
-                        let mut ai = self.parse(tok_it, 0, &mt);
-                        while let Some(Token::Comma) = tok_it.peek() {
-                            tok_it.next();
-                            args.push(ai);
-                            ai = self.parse(tok_it, 0, &mt);
-                        }
-                        let Some(Token::RParen) = tok_it.next() else {
-                            panic!("Unmatched open paren")
-                        };
-                        args.push(ai);
-                        Node::new_funct(&lnum, left, args)
+                        let args = self.parse_args(tok_it);
+                        Node::new_funct(&cover(&tok_it.span, &left, &Node::Empty), left, args)
                     },
-                    _ => Node::new_postfix(&lnum, &op, left),
+                    // Member access
+                    Token::Dot | Token::Arrow => match self.parse_name(tok_it) {
+                        Some(field) => Node::new_member(&cover(&tok_it.span, &left, &Node::Empty), &op, left, &field),
+                        None => Node::new_invalid(&cover(&op_span, &left, &Node::Empty)),
+                    },
+                    _ => Node::new_postfix(&cover(&op_span, &left, &Node::Empty), &op, left),
                 };
                 continue;
             }
             // Get the binding power, or return if a close-bracket is detected
-            if let Some((lbp, rbp)) = self.get_infix_bp(&op, match_tok, lnum) {
+            if let Some((lbp, rbp)) = self.get_infix_bp(&op, match_tok) {
                 // The subtree to the left of this is more strongly attracted to the previous operator
                 if lbp < min_bp {
                     return left;
                 }
                 // Advance the main iterator once we are sure we will consume this token
                 tok_it.next();
+                // The conditional operator is an if expression. The middle operand is bracketed
+                // by the ? and :, so is parsed as a whole expression
+                if let Token::Question = op {
+                    let mut tmatch_tok = match_tok.clone();
+                    tmatch_tok.insert(Token::Colon);
+                    let t_expr = self.parse_scoped(tok_it, 2, &tmatch_tok);
+                    if !self.expect(tok_it, Token::Colon) {
+                        self.synchronize(tok_it, match_tok);
+                        left = Node::new_invalid(&cover(&tok_it.span, &left, &Node::Empty));
+                        continue;
+                    }
+                    let f_expr = self.parse_scoped(tok_it, rbp, match_tok);
+                    left = Node::new_if(&cover(&op_span, &left, &f_expr), left, t_expr, f_expr);
+                    continue;
+                }
                 // Now that we have consumed it, we can increment our number of open brackets
-                // Calculate the right subtree
-                let right = self.parse(tok_it, rbp, match_tok);
+                // Calculate the right subtree. The rhs of a logical operator and the body of a
+                // function are scoped
+                let scoped = matches!(op, Token::And | Token::Or) || (op == Token::Assign && bodiless_funct_decl(&mut left).is_some());
+                let right = if scoped {
+                    self.parse_scoped(tok_it, rbp, match_tok)
+                } else {
+                    self.parse(tok_it, rbp, match_tok)
+                };
                 if let Token::Semi = op {
                     if self.open_bracks != 0 {
-                        panic!("Unmatched open brackets in this expression")
+                        self.error(ParseErrorKind::SemiInBrackets, &op_span);
                     }
-                    left = Node::new_statement(&lnum, left, right);
+                    left = Node::new_statement(&cover(&op_span, &left, &right), left, right);
+                } else if let (Token::Assign, Some(f)) = (&op, bodiless_funct_decl(&mut left)) {
+                    // Assigning to a function declaration defines its body
+                    let span = cover(&op_span, &Node::Empty, &right);
+                    f.span = f.span.to(&span);
+                    f.body = right;
+                    // Modifiers applied to the declaration cover the body too
+                    let mut modified = &mut left;
+                    while let Node::PrefixOp(m) = modified {
+                        m.span = m.span.to(&span);
+                        modified = &mut m.rhs;
+                    }
+                } else if let Token::And | Token::Or = op {
+                    left = Node::new_logical(&cover(&op_span, &left, &right), &op, left, right);
                 } else {
-                    left = Node::new_infix(&lnum, &op, left, right);
+                    left = Node::new_infix(&cover(&op_span, &left, &right), &op, left, right);
                 }
                 continue;
             }
-            println!("Returning on token {:?}", op);
-            // If we reach here, it means the token is something we want to ignore
-            return left;
+            if match_tok.contains(&op) {
+                // If we reach here, it means the token is something we want to ignore
+                return left;
+            }
+            // Anything else can't follow an expression. Skip to the end of the statement, or
+            // discard the token if it is a closing brace which doesn't match anything
+            self.error(ParseErrorKind::UnexpectedToken(op.clone()), &op_span);
+            if op == Token::RCurly {
+                tok_it.next();
+            } else {
+                self.synchronize(tok_it, match_tok);
+            }
         }
         self.eof_read = true;
         left
@@ -219,9 +1087,14 @@ impl Parser {
         }
         let ret = match tok {
             Token::Ret => ((), 4),
-            Token::Inc | Token::Dec => ((), 28),
-            Token::IntKey | Token::CharKey => ((), 34),
-            _ => panic!("Bad prefix operator : {:?}\nCurrent end_tok : {:?}", tok, end_tok),
+            Token::Inc | Token::Dec | Token::Tilde | Token::Sub | Token::Add | Token::Not => ((), 28),
+            // Dereference and address-of bind more tightly than assignment, so *p = 3 assigns
+            // through the pointer, but less tightly than indexing and postfix steps, so &a[1] is
+            // the address of an element and *p++ steps the pointer
+            Token::Star | Token::Amp => ((), 33),
+            t if t.is_type_spec() => ((), 34),
+            t if t.is_modifier() => ((), 34), // Modifiers bind like type specifiers
+            _ => return None,
         };
         Some(ret)
     }
@@ -231,9 +1104,12 @@ impl Parser {
             return None;
         }
         let ret = match tok {
-            Token::LBrack => (30, ()), // Think of array subscript as an postfix operator
+            // Think of array subscript as an postfix operator. It binds less tightly than a type
+            // specifier, so the brackets of int a[3] apply to the whole declaration
+            Token::LBrack => (33, ()),
             Token::LParen => (36, ()), // Function call postfix operator has high precedence
-            Token::Inc | Token::Dec => (30, ()),
+            Token::Dot | Token::Arrow => (36, ()), // Member access binds as tightly as a function call
+            Token::Inc | Token::Dec => (33, ()),
             _ => return None,
         };
         Some(ret)
@@ -241,7 +1117,7 @@ impl Parser {
 
     // Return the left and right binding powers of an infix operator. Different precedence levels
     // correspond to even binding power values. Odd values are used to represent associativity
-    fn get_infix_bp(&mut self, tok : & Token, end_tok : & HashSet<Token>, lnum : u64) -> Option<(u32, u32)> {
+    fn get_infix_bp(&mut self, tok : & Token, end_tok : & HashSet<Token>) -> Option<(u32, u32)> {
         if end_tok.contains(tok) {
             return None;
         }
@@ -252,17 +1128,531 @@ impl Parser {
                 | Token::AddAss 
                 | Token::SubAss 
                 | Token::MulAss 
-                | Token::DivAss => (32, 4), // assignment operators are right associative
+                | Token::DivAss
+                | Token::ModAss
+                | Token::AndAss
+                | Token::OrAss
+                | Token::XorAss
+                | Token::ShlAss
+                | Token::ShrAss => (32, 4), // assignment operators are right associative
+            Token::Question => (6, 5), // The conditional operator is right associative
+            Token::Or => (8, 9),
+            Token::And => (10, 11),
+            Token::Pipe => (12, 13),
+            Token::Caret => (14, 15),
+            Token::Amp => (16, 17),
             Token::Equal | Token::NotEq => (18, 19),
             Token::GT | Token::GE | Token::LT | Token::LE => (20, 21),
+            Token::Shl | Token::Shr => (22, 23),
             Token::Add | Token::Sub => (24, 25),
-            Token::Star | Token::Div => (26, 27),
-            _ => panic!("Bad binary operator on line {}: {:?}\nCurrent end_tok : {:?}",lnum, tok, end_tok),
+            Token::Star | Token::Div | Token::Mod => (26, 27),
+            _ => return None,
         };
         Some(ret)
     }
 
 }
 
+// Check if the source text of a node ends in a closing curly brace
+fn ends_with_block(n : &Node) -> bool {
+    match n {
+        Node::Block(_) => true,
+        Node::While(w) => ends_with_block(&w.body),
+        Node::For(f) => ends_with_block(&f.body),
+        Node::FunctDecl(f) => ends_with_block(&f.body),
+        Node::Lambda(l) => ends_with_block(&l.body),
+        Node::StructDecl(_) | Node::EnumDecl(_) | Node::Match(_) | Node::Switch(_) => true,
+        Node::If(i) => if let Node::Empty = i.f_expr {
+            ends_with_block(&i.t_expr)
+        } else {
+            ends_with_block(&i.f_expr)
+        },
+        _ => false,
+    }
+}
+
+// Check if a node declares a variable, possibly with modifiers applied to it
+fn is_declaration(n : &Node) -> bool {
+    match n {
+        Node::PrefixOp(op) if op.op_type.is_modifier() => is_declaration(&op.rhs),
+        Node::PrefixOp(op) => op.op_type.is_type_spec(),
+        Node::ArrayDecl(_) => true,
+        _ => false,
+    }
+}
+
+// Return the function declaration without a body which n declares, possibly with modifiers applied
+// to it, as in static int f()
+fn bodiless_funct_decl(n : &mut Node) -> Option<&mut ast::FunctDecl> {
+    match n {
+        Node::FunctDecl(f) if matches!(f.body, Node::Empty) => Some(f),
+        Node::PrefixOp(op) if op.op_type.is_modifier() => bodiless_funct_decl(&mut op.rhs),
+        _ => None,
+    }
+}
+
+// Return the type specifier for the struct or enum type with the given name
+fn tagged_type(key : &Token, name : String) -> Token {
+    match key {
+        Token::StructKey => Token::StructType(name),
+        _ => Token::EnumType(name),
+    }
+}
+
+// Return the declaration of an array of the elements declared by decl. Modifiers apply to the
+// whole array, so const int a[3] is const (int a[3])
+fn array_decl(span : &Span, decl : Node, len : Node) -> Node {
+    match decl {
+        Node::PrefixOp(mut op) if op.op_type.is_modifier() => {
+            op.rhs = array_decl(span, op.rhs, len);
+            op.span = span.clone();
+            Node::PrefixOp(op)
+        },
+        decl => Node::new_array_decl(span, decl, len),
+    }
+}
+
+// Add a type specifier to the start of a return type. The specifiers before a function type apply
+// to its return type, so unsigned int(int) f returns unsigned int
+fn add_ret_spec(ret_type : &mut Vec<Token>, spec : Token) {
+    match ret_type.first_mut() {
+        Some(Token::FunctType(inner, _)) => add_ret_spec(inner, spec),
+        _ => ret_type.insert(0, spec),
+    }
+}
+
+// Check if the stars of a pointer declarator are applied to a call, and so declare a function
+// returning a pointer
+fn is_pointer_funct(op : &ast::PrefixOp) -> bool {
+    match &op.rhs {
+        Node::Funct(_) => op.op_type == Token::Star,
+        Node::PrefixOp(inner) => op.op_type == Token::Star && is_pointer_funct(inner),
+        _ => false,
+    }
+}
+
+// Return the name of the variable declared by a declaration, such as int a or int a[]
+fn declaration_name(n : &Node) -> Option<&str> {
+    match n {
+        Node::PrefixOp(op) if op.op_type.is_type_spec() => declaration_name(&op.rhs).or(declarator_name(&op.rhs)),
+        Node::PrefixOp(op) if op.op_type.is_modifier() => declaration_name(&op.rhs),
+        Node::ArrayDecl(decl) => declaration_name(&decl.decl),
+        _ => None,
+    }
+}
+
+// Return the name declared by a declarator, such as a or *a
+fn declarator_name(n : &Node) -> Option<&str> {
+    match n {
+        Node::Id(id) => Some(&id.name),
+        Node::PrefixOp(op) if op.op_type == Token::Star => declarator_name(&op.rhs),
+        _ => None,
+    }
+}
+
+// Check if a token can only appear at the start of an expression, and never after one
+fn starts_expression(tok : &Token) -> bool {
+    matches!(tok,
+        Token::IntConst(..)
+        | Token::CharConst(_)
+        | Token::StrConst(_)
+        | Token::Id(_)
+        | Token::LCurly
+        | Token::If
+        | Token::While
+        | Token::For
+        | Token::Break
+        | Token::Continue
+        | Token::Ret
+        | Token::FloatConst(..)
+        | Token::BoolConst(_)
+        | Token::IntKey
+        | Token::CharKey
+        | Token::LongKey
+        | Token::ShortKey
+        | Token::UnsignedKey
+        | Token::BoolKey
+        | Token::FloatKey
+        | Token::DoubleKey
+        | Token::StructKey
+        | Token::EnumKey
+        | Token::Match
+        | Token::Switch
+        | Token::Const
+        | Token::Static
+        | Token::Volatile
+        | Token::Pub
+        | Token::Import
+        | Token::Typedef
+        | Token::TypeKey
+        | Token::Sizeof
+        | Token::Not
+        | Token::Tilde)
+}
+
+// Check if a token can only appear after the end of an expression
+fn closes_expression(tok : &Token) -> bool {
+    matches!(tok,
+        Token::Semi
+        | Token::RCurly
+        | Token::RParen
+        | Token::RBrack
+        | Token::Comma
+        | Token::Colon
+        | Token::Else)
+}
+
+// Return the span covering both nodes, as well as the span of the operator joining them. Empty
+// operands do not contribute to the span
+fn cover(op : &Span, lhs : &Node, rhs : &Node) -> Span {
+    let start = lhs.span().unwrap_or(op);
+    let end = rhs.span().unwrap_or(op);
+    start.to(op).to(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parse source, returning the ast and the kinds of the syntax errors found
+    fn parse(source : &str) -> (Node, Vec<ParseErrorKind>) {
+        let mut scanner = Scanner::new(source.as_bytes(), "test.c");
+        let (ast, errors) = Parser::new().gen_ast(&mut scanner);
+        (ast, errors.into_iter().map(|e| e.kind).collect())
+    }
+
+    // Return the expressions of the statements at the top level of a program
+    fn statements(mut n : &Node) -> Vec<&Node> {
+        let mut exprs = Vec::new();
+        while let Node::Statement(s) = n {
+            exprs.push(&s.expr);
+            n = &s.next;
+        }
+        if !matches!(n, Node::Empty) {
+            exprs.push(n);
+        }
+        exprs
+    }
+
+    #[test]
+    fn parsing_recovers_after_each_syntax_error() {
+        let (ast, errors) = parse("int a = ;\nint b = 3 4;\nfoo(1, 2;\nint c = 5;");
+        assert!(matches!(errors.as_slice(), [
+            ParseErrorKind::ExpectedExpression(Some(Token::Semi)),
+            ParseErrorKind::UnexpectedToken(Token::IntConst(4, ..)),
+            ParseErrorKind::Unclosed { close : Token::RParen, .. },
+        ]), "{errors:?}");
+        let last = *statements(&ast).last().expect("No statements");
+        assert_eq!(grouped(last), "((IntKey c) Assign 5)");
+    }
+
+    // Return an expression with each operator and its operands parenthesised, to show how it was
+    // grouped
+    fn grouped(n : &Node) -> String {
+        match n {
+            Node::Id(id) => id.name.clone(),
+            Node::Int(i) => i.val.to_string(),
+            Node::InfixOp(op) => format!("({} {:?} {})", grouped(&op.lhs), op.op_type, grouped(&op.rhs)),
+            Node::Logical(op) => format!("({} {:?} {})", grouped(&op.lhs), op.op_type, grouped(&op.rhs)),
+            Node::PrefixOp(op) => format!("({:?} {})", op.op_type, grouped(&op.rhs)),
+            Node::PostfixOp(op) => format!("({} {:?})", grouped(&op.lhs), op.op_type),
+            Node::If(i) => format!("({} ? {} : {})", grouped(&i.cond), grouped(&i.t_expr), grouped(&i.f_expr)),
+            _ => panic!("Not an operator expression"),
+        }
+    }
+
+    #[test]
+    fn binary_operators_follow_c_precedence() {
+        let (ast, errors) = parse("a + b * c << d & e | f ^ g; a < b == c >= d; a % b - c / d >> e; a -= b <<= c");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs : Vec<_> = statements(&ast).into_iter().map(grouped).collect();
+        assert_eq!(exprs, [
+            "((((a Add (b Star c)) Shl d) Amp e) Pipe (f Caret g))",
+            "((a LT b) Equal (c GE d))",
+            "(((a Mod b) Sub (c Div d)) Shr e)",
+            "(a SubAss (b ShlAss c))",
+        ]);
+    }
+
+    #[test]
+    fn logical_operators_are_their_own_nodes() {
+        let (ast, errors) = parse("a || b && c | d; a = b && c");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        assert!(matches!(exprs[0], Node::Logical(op) if op.op_type == Token::Or));
+        assert_eq!(grouped(exprs[0]), "(a Or (b And (c Pipe d)))");
+        assert_eq!(grouped(exprs[1]), "(a Assign (b And c))");
+    }
+
+    #[test]
+    fn while_loops_are_expressions() {
+        let (ast, errors) = parse("int x = while (a) b; c = 1 + while (a) { b } * 2");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::InfixOp(assn) = exprs[0] else { panic!("Not an assignment") };
+        assert!(matches!(assn.rhs, Node::While(_)));
+        // The body extends as far as the expression it is part of
+        let Node::InfixOp(assn) = exprs[1] else { panic!("Not an assignment") };
+        let Node::InfixOp(add) = &assn.rhs else { panic!("Not an addition") };
+        let Node::While(w) = &add.rhs else { panic!("Not a while loop") };
+        assert!(matches!(&w.body, Node::InfixOp(mul) if mul.op_type == Token::Star));
+    }
+
+    #[test]
+    fn for_loops_have_three_clauses() {
+        let (ast, errors) = parse("for (int i = 0; i < 3; i++) x += i; for (;;) x");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::For(f) = exprs[0] else { panic!("Not a for loop") };
+        assert_eq!(grouped(&f.init), "((IntKey i) Assign 0)");
+        assert_eq!(grouped(&f.cond), "(i LT 3)");
+        assert_eq!(grouped(&f.step), "(i Inc)");
+        let Node::For(f) = exprs[1] else { panic!("Not a for loop") };
+        assert!(matches!((&f.init, &f.cond, &f.step), (Node::Empty, Node::Empty, Node::Empty)));
+        let (_, errors) = parse("for (int i = 0) x");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::Expected { expected : Token::Semi, .. }]), "{errors:?}");
+    }
+
+    #[test]
+    fn jumps_take_labels_of_enclosing_loops() {
+        let (ast, errors) = parse("int outer = 1; outer: while (1) for (;;) { break outer 3; continue outer; break outer }");
+        assert!(errors.is_empty(), "{errors:?}");
+        let Node::While(w) = statements(&ast)[1] else { panic!("Not a while loop") };
+        assert_eq!(w.label.as_deref(), Some("outer"));
+        let Node::For(f) = &w.body else { panic!("Not a for loop") };
+        let Node::Block(b) = &f.body else { panic!("Not a block") };
+        let jumps = statements(&b.statements);
+        assert!(matches!(jumps[0], Node::Break(b) if b.label.as_deref() == Some("outer") && matches!(b.val, Node::Int(_))));
+        assert!(matches!(jumps[1], Node::Continue(c) if c.label.as_deref() == Some("outer")));
+        assert!(matches!(jumps[2], Node::Break(b) if b.label.is_some() && matches!(b.val, Node::Empty)));
+        // Outside the loop, outer is the variable rather than the label
+        let (ast, errors) = parse("outer: while (1) 1; while (1) break outer");
+        assert!(errors.is_empty(), "{errors:?}");
+        let Node::While(w) = statements(&ast)[1] else { panic!("Not a while loop") };
+        assert!(matches!(&w.body, Node::Break(b) if b.label.is_none() && matches!(b.val, Node::Id(_))));
+        // A name before the colon of a conditional or case is a value
+        let (ast, errors) = parse("int y = c ? x : while (x) x = 0;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let Node::InfixOp(init) = statements(&ast)[0] else { panic!("Not an assignment") };
+        assert!(matches!(&init.rhs, Node::If(i) if matches!(i.t_expr, Node::Id(_)) && matches!(i.f_expr, Node::While(_))));
+        let (_, errors) = parse("switch (c) { case k: while (x) x = 0; default: 1 }");
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn type_specifiers_applied_to_calls_declare_functions() {
+        let (ast, errors) = parse("unsigned long f(int a, char *b) = a; int g(); g(1, 2)");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::FunctDecl(f) = exprs[0] else { panic!("Not a function declaration") };
+        assert_eq!((f.name.as_str(), f.ret_type.as_slice()), ("f", [Token::UnsignedKey, Token::LongKey].as_slice()));
+        let params : Vec<_> = f.params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(params, ["a", "b"]);
+        assert!(matches!(f.body, Node::Id(_)));
+        assert!(matches!(exprs[1], Node::FunctDecl(g) if g.params.is_empty() && matches!(g.body, Node::Empty)));
+        assert!(matches!(exprs[2], Node::Funct(_)));
+        let (ast, errors) = parse("char **f(int a) = 0;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let Node::FunctDecl(f) = statements(&ast)[0] else { panic!("Not a function declaration") };
+        assert_eq!(f.ret_type, [Token::CharKey, Token::Star, Token::Star]);
+        let (_, errors) = parse("int 3(int a); int g(3);");
+        assert_eq!(errors, [ParseErrorKind::InvalidFunctName, ParseErrorKind::InvalidParam]);
+    }
+
+    #[test]
+    fn brackets_after_a_declaration_declare_an_array() {
+        let (ast, errors) = parse("int a[2][3]; char s[] = \"hi\"; a[1][2]");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        // The outer brackets give the length of the inner dimension
+        let Node::ArrayDecl(outer) = exprs[0] else { panic!("Not an array declarator") };
+        let Node::ArrayDecl(inner) = &outer.decl else { panic!("Not an array declarator") };
+        assert!(matches!((&inner.len, &outer.len), (Node::Int(i), Node::Int(o)) if i.val == 2 && o.val == 3));
+        assert_eq!(declaration_name(exprs[0]), Some("a"));
+        let Node::InfixOp(assn) = exprs[1] else { panic!("Not an assignment") };
+        assert!(matches!(&assn.lhs, Node::ArrayDecl(s) if matches!(s.len, Node::Empty)));
+        // Outside a declaration, brackets index
+        assert_eq!(grouped(exprs[2]), "((a LBrack 1) LBrack 2)");
+    }
+
+    // Return the operator and operand of a prefix node
+    fn prefix(n : &Node) -> (&Token, &Node) {
+        match n {
+            Node::PrefixOp(op) => (&op.op_type, &op.rhs),
+            _ => panic!("Not a prefix operator"),
+        }
+    }
 
+    #[test]
+    fn unary_operators_bind_tighter_than_binary_ones() {
+        let (ast, errors) = parse("-a * b; !a == ~b; a - -b; -f(x)");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        assert_eq!(grouped(exprs[0]), "((Sub a) Star b)");
+        assert_eq!(grouped(exprs[1]), "((Not a) Equal (Tilde b))");
+        assert_eq!(grouped(exprs[2]), "(a Sub (Sub b))");
+        assert!(matches!(prefix(exprs[3]).1, Node::Funct(_)));
+    }
+
+    #[test]
+    fn conditional_operator_is_right_associative() {
+        let (ast, errors) = parse("a ? b : c ? d : e; x = a || b ? c = 1 : d; a ? b ? c : d : e");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs : Vec<_> = statements(&ast).into_iter().map(grouped).collect();
+        assert_eq!(exprs, [
+            "(a ? b : (c ? d : e))",
+            "(x Assign ((a Or b) ? (c Assign 1) : d))",
+            "(a ? (b ? c : d) : e)",
+        ]);
+        let (_, errors) = parse("a ? b; c");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::Expected { expected : Token::Colon, .. }]), "{errors:?}");
+    }
 
+    #[test]
+    fn modifiers_are_prefix_operators() {
+        let (ast, errors) = parse("const int y = 8; static volatile x; const x = 3");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::InfixOp(assn) = exprs[0] else { panic!("Not an assignment") };
+        let (modifier, decl) = prefix(&assn.lhs);
+        assert_eq!((modifier, prefix(decl).0), (&Token::Const, &Token::IntKey));
+        let (modifier, target) = prefix(exprs[1]);
+        assert_eq!((modifier, prefix(target).0), (&Token::Static, &Token::Volatile));
+        assert_eq!(grouped(exprs[2]), "((Const x) Assign 3)");
+    }
+
+    #[test]
+    fn struct_definitions_literals_and_members() {
+        let (ast, errors) = parse("struct P { int x; struct P *next; } struct P p = P { x : 1 }; p.next->x");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::StructDecl(decl) = exprs[0] else { panic!("Not a struct definition") };
+        let fields : Vec<_> = decl.fields.iter().map(|f| declaration_name(f)).collect();
+        assert_eq!((decl.name.as_str(), fields), ("P", vec![Some("x"), Some("next")]));
+        let Node::InfixOp(assn) = exprs[1] else { panic!("Not an assignment") };
+        assert_eq!(*prefix(&assn.lhs).0, Token::StructType("P".to_string()));
+        assert!(matches!(&assn.rhs, Node::StructLit(lit) if lit.fields.len() == 1 && lit.fields[0].name == "x"));
+        let Node::Member(arrow) = exprs[2] else { panic!("Not a member access") };
+        assert!(matches!(&arrow.lhs, Node::Member(dot) if dot.op_type == Token::Dot && dot.field == "next"));
+        let (_, errors) = parse("struct Q { int x; 3; };");
+        assert_eq!(errors, [ParseErrorKind::InvalidField]);
+    }
+
+    #[test]
+    fn enum_definitions_and_match_arms() {
+        let (ast, errors) = parse("enum Shape { Circle(int), Rect(int, long *), Empty } match (s) { Circle(r) => r, Rect(_, h) => *h, _ => 0 }");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::EnumDecl(decl) = exprs[0] else { panic!("Not an enum definition") };
+        let arities : Vec<_> = decl.variants.iter().map(|v| (v.name.as_str(), v.fields.len())).collect();
+        assert_eq!(arities, [("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+        assert_eq!(decl.variants[1].fields[1].pointers, 1);
+        let Node::Match(m) = exprs[1] else { panic!("Not a match") };
+        let arms : Vec<_> = m.arms.iter().map(|a| (a.variant.as_deref(), a.bindings.len())).collect();
+        assert_eq!(arms, [(Some("Circle"), 1), (Some("Rect"), 2), (None, 0)]);
+        let (_, errors) = parse("enum E { A(3) };");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::ExpectedTypeName(Some(Token::IntConst(3, ..)))]), "{errors:?}");
+        let (_, errors) = parse("match (s) { A 1 };");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::Expected { expected : Token::FatArrow, .. }]), "{errors:?}");
+    }
+
+    #[test]
+    fn type_aliases_become_type_specifiers() {
+        let (ast, errors) = parse("typedef int *IntPtr; type Num = unsigned long; IntPtr p; Num *n; Num * 2");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        assert!(matches!(exprs[0], Node::TypeDef(t) if t.name == "IntPtr" && matches!(t.aliased, ast::Aliased::Decl(_))));
+        assert!(matches!(exprs[1], Node::TypeDef(t) if t.name == "Num" && matches!(&t.aliased, ast::Aliased::TypeName(n) if n.specs.len() == 2)));
+        assert_eq!(*prefix(exprs[2]).0, Token::TypeName("IntPtr".to_string()));
+        let (spec, declarator) = prefix(exprs[3]);
+        assert_eq!((spec, prefix(declarator).0), (&Token::TypeName("Num".to_string()), &Token::Star));
+        // A type name followed by an operator is still a declaration, and 2 can't be declared
+        assert!(matches!(prefix(exprs[4]).1, Node::PrefixOp(_)));
+        let (_, errors) = parse("typedef 3; type T = 4;");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::InvalidTypedef, ParseErrorKind::ExpectedTypeName(_)]), "{errors:?}");
+        // Aliases declared in a branch or loop body are scoped to it, like variables
+        for source in ["if (c) typedef int T; T * x", "while (c) type T = int; T * x", "c && (type T = int); T * x"] {
+            let (ast, errors) = parse(source);
+            assert!(errors.is_empty(), "{errors:?}");
+            assert!(matches!(statements(&ast)[1], Node::InfixOp(op) if op.op_type == Token::Star), "{source}");
+        }
+    }
+
+    #[test]
+    fn parenthesised_type_names_are_casts_or_sizeof_operands() {
+        let (ast, errors) = parse("(long) x + 1; (x * y); sizeof(struct P *); sizeof x++; sizeof (x)");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let Node::InfixOp(add) = exprs[0] else { panic!("Not an addition") };
+        assert!(matches!(&add.lhs, Node::Cast(c) if c.type_name.specs == [Token::LongKey]));
+        assert!(matches!(exprs[1], Node::InfixOp(_)));
+        assert!(matches!(exprs[2], Node::Sizeof(s) if matches!(&s.arg, ast::SizeofArg::TypeName(t) if t.pointers == 1)));
+        assert!(matches!(exprs[3], Node::Sizeof(s) if matches!(&s.arg, ast::SizeofArg::Expr(Node::PostfixOp(_)))));
+        assert!(matches!(exprs[4], Node::Sizeof(s) if matches!(s.arg, ast::SizeofArg::Expr(_))));
+    }
+
+    #[test]
+    fn dereference_binds_less_tightly_than_postfix_operators() {
+        let (ast, errors) = parse("&a[1]; *p++; *p = 3;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let (op, rhs) = prefix(exprs[0]);
+        assert_eq!(*op, Token::Amp);
+        assert!(matches!(rhs, Node::InfixOp(index) if index.op_type == Token::LBrack));
+        let (op, rhs) = prefix(exprs[1]);
+        assert_eq!(*op, Token::Star);
+        assert!(matches!(rhs, Node::PostfixOp(step) if step.op_type == Token::Inc));
+        let Node::InfixOp(assign) = exprs[2] else { panic!("Not an assignment") };
+        assert_eq!(assign.op_type, Token::Assign);
+        assert_eq!(*prefix(&assign.lhs).0, Token::Star);
+    }
+
+    #[test]
+    fn stars_in_declarators_declare_pointers() {
+        let (ast, errors) = parse("int *a[3]; int **p;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        // An array of pointers, rather than a pointer to an indexed element
+        let Node::ArrayDecl(decl) = exprs[0] else { panic!("Not an array declarator") };
+        let (spec, declarator) = prefix(&decl.decl);
+        assert_eq!((spec, prefix(declarator).0), (&Token::IntKey, &Token::Star));
+        let (_, declarator) = prefix(exprs[1]);
+        let (star, inner) = prefix(declarator);
+        assert_eq!((star, prefix(inner).0), (&Token::Star, &Token::Star));
+        assert_eq!(declaration_name(exprs[1]), Some("p"));
+    }
+
+    #[test]
+    fn parameters_without_a_body_name_a_function_type() {
+        let (ast, errors) = parse("unsigned int(int, char *c) f; int(int)(int) g(int a) = 1; int(int c) => c;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let exprs = statements(&ast);
+        let int_to_int = Token::FunctType(vec![Token::IntKey], vec![vec![Token::IntKey]]);
+        let (spec, _) = prefix(exprs[0]);
+        assert_eq!(*spec, Token::FunctType(
+            vec![Token::UnsignedKey, Token::IntKey],
+            vec![vec![Token::IntKey], vec![Token::CharKey, Token::Star]],
+        ));
+        assert_eq!(declaration_name(exprs[0]), Some("f"));
+        let Node::FunctDecl(f) = exprs[1] else { panic!("Not a function declaration") };
+        assert_eq!(f.ret_type, [Token::FunctType(vec![int_to_int], vec![vec![Token::IntKey]])]);
+        assert!(matches!(exprs[2], Node::Lambda(_)));
+        let (_, errors) = parse("int(3) f;");
+        assert!(matches!(errors.as_slice(), [ParseErrorKind::InvalidParam]), "{errors:?}");
+    }
+
+    #[test]
+    fn switch_cases_must_have_values() {
+        let (ast, errors) = parse("switch (1) { case : 5; case 1, : 6 }");
+        assert!(matches!(errors.as_slice(), [
+            ParseErrorKind::ExpectedExpression(Some(Token::Colon)),
+            ParseErrorKind::ExpectedExpression(Some(Token::Colon)),
+        ]), "{errors:?}");
+        let Node::Switch(switch) = statements(&ast)[0] else { panic!("Not a switch") };
+        assert_eq!(switch.arms.len(), 2);
+        // A switch ends in a block, so needs no semicolon before the next statement
+        let (ast, errors) = parse("switch (x) { default: 1 } int y = 2;");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(statements(&ast).len(), 2);
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use super::{span::Span, token::IntSuffix};
+
+// The problems the scanner can encounter in the source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexErrorKind {
+    UnrecognizedChar(char),
+    UnterminatedStr,
+    UnterminatedChar,
+    EmptyChar,
+    OversizedChar, // More than one character between single quotes
+    UnterminatedComment,
+    UnknownEscape(char),
+    BadHexEscape, // \x not followed by 2 hex digits
+    BadUnicodeEscape, // \u not followed by {...} containing a valid unicode scalar value
+    InvalidDigit(char, u32), // A digit which is not valid in the radix of the constant
+    MissingDigits, // A radix prefix with no digits after it
+    InvalidIntSuffix(String),
+    IntOutOfRange { suffix : IntSuffix, radix : u32 },
+    MissingExponent, // An exponent marker with no digits after it
+    InvalidFloatSuffix(String),
+    FloatOutOfRange,
+    Io(String),
+}
+
+// A lexical error, along with where it occurred. The scanner emits a Token::INVAL in place of the
+// offending text and records one of these, so that scanning can continue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind : LexErrorKind,
+    pub span : Span,
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorKind::UnrecognizedChar(c) => write!(f, "unrecognized character {c:?}"),
+            LexErrorKind::UnterminatedStr => write!(f, "no matching double quote to end string constant"),
+            LexErrorKind::UnterminatedChar => write!(f, "no matching single quote to close char constant"),
+            LexErrorKind::EmptyChar => write!(f, "empty char constant"),
+            LexErrorKind::OversizedChar => write!(f, "char constant contains more than one character"),
+            LexErrorKind::UnterminatedComment => write!(f, "unterminated block comment"),
+            LexErrorKind::UnknownEscape(c) => write!(f, "unknown escape sequence \\{c}"),
+            LexErrorKind::BadHexEscape => write!(f, "\\x escape must be followed by exactly 2 hex digits"),
+            LexErrorKind::BadUnicodeEscape => write!(f, "\\u escape must be of the form \\u{{...}} with up to 6 hex digits forming a valid unicode character"),
+            LexErrorKind::InvalidDigit(c, radix) => write!(f, "invalid digit {c:?} in base {radix} integer constant"),
+            LexErrorKind::MissingDigits => write!(f, "integer constant has no digits after its prefix"),
+            LexErrorKind::InvalidIntSuffix(suffix) => write!(f, "invalid suffix {suffix:?} on integer constant"),
+            LexErrorKind::IntOutOfRange { suffix : IntSuffix::None, radix } => write!(f, "integer constant is too large, the maximum value without a suffix is {}", IntSuffix::None.max(*radix)),
+            LexErrorKind::IntOutOfRange { suffix, radix } => write!(f, "integer constant is too large, the maximum value with suffix {suffix} is {}", suffix.max(*radix)),
+            LexErrorKind::MissingExponent => write!(f, "floating point constant has no digits in its exponent"),
+            LexErrorKind::InvalidFloatSuffix(suffix) => write!(f, "invalid suffix {suffix:?} on floating point constant"),
+            LexErrorKind::FloatOutOfRange => write!(f, "floating point constant is too large"),
+            LexErrorKind::Io(e) => write!(f, "problem reading file: {e}"),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
@@ -1,60 +1,198 @@
 use std::collections::VecDeque;
-use std::io::{BufRead, BufReader, Lines, Read};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
 use std::iter::Peekable;
+use std::rc::Rc;
 use std::str::Chars;
 
-use token::Token;
+use error::{LexError, LexErrorKind};
+use span::Span;
+use token::{FloatSuffix, FloatVal, IntSuffix, Token};
 
+pub mod error;
+pub mod span;
 pub mod token;
 
+// A peekable iterator over the characters of a single line, which also tracks the column and byte
+// offset of the next character so that tokens can be given a span
+#[derive(Clone)]
+struct LineCursor<'a> {
+    chars : Peekable<Chars<'a>>,
+    col : u64,
+    byte : usize,
+}
+
+impl<'a> LineCursor<'a> {
+    fn new(line : &'a str) -> Self {
+        Self {
+            chars : line.chars().peekable(),
+            col : 1,
+            byte : 0,
+        }
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+}
+
+impl Iterator for LineCursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let x = self.chars.next()?;
+        self.col += 1;
+        self.byte += x.len_utf8();
+        Some(x)
+    }
+}
+
 pub struct Scanner<T : Read> {
-    tokens : VecDeque<Token>,
-    lines : Lines<BufReader<T>>,
+    tokens : VecDeque<(Token, Span)>,
+    input : BufReader<T>,
+    file : Rc<str>,
+    line_start : usize, // Byte offset of the start of the current line
+    comment_depth : u32, // Number of block comments we are currently nested inside of
+    comment_start : Span, // Span of the opening of the outermost unclosed block comment
+    eof_read : bool,
+    errors : Vec<LexError>,
+    token_errors : Vec<LexErrorKind>, // Errors found in the token currently being read
     pub lnum : u64,
+    pub span : Span, // Span of the token most recently returned by next
 }
 
 impl<T : Read> Iterator for Scanner<T> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Fill the token buffer if it is empty
-        while self.tokens.is_empty() {
-            self.lnum += 1;
+        let (tok, span) = self.next_spanned()?;
+        self.span = span;
+        Some(tok)
+    }
+}
+
+impl<T : Read> Scanner<T> {
+    pub fn new(input : T, file : &str) -> Self {
+        Self {
+            tokens : VecDeque::new(),
+            input : BufReader::new(input),
+            file : Rc::from(file),
+            line_start : 0,
+            comment_depth : 0,
+            comment_start : Span::default(),
+            eof_read : false,
+            errors : Vec::new(),
+            token_errors : Vec::new(),
+            lnum : 0,
+            span : Span::default(),
+        }
+    }
+
+    // Return the next token along with its span. Unlike next, this does not update self.span
+    pub fn next_spanned(&mut self) -> Option<(Token, Span)> {
+        self.fill(1);
+        self.tokens.pop_front()
+    }
+
+    // Check next token without advancing
+    pub fn peek(&mut self) -> Option<Token> {
+        self.peek_nth(0)
+    }
+
+    // Check the token n tokens past the next one without advancing
+    pub fn peek_nth(&mut self, n : usize) -> Option<Token> {
+        self.fill(n + 1);
+        self.tokens.get(n).map(|(t, _)| t.clone())
+    }
+
+    // Check the span of the next token without advancing
+    pub fn peek_span(&mut self) -> Option<Span> {
+        self.fill(1);
+        self.tokens.front().map(|(_, s)| s.clone())
+    }
+
+    // Return the lexical errors encountered so far, in the order they were found
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
+    // Fill the token buffer until it has at least n tokens, scanning lines until enough tokens are
+    // found or the input runs out
+    fn fill(&mut self, n : usize) {
+        while self.tokens.len() < n && !self.eof_read {
             // Scan this line if queue empty
-            let Some(line) = self.lines.next() else {return None};
+            let Some((line, len)) = self.read_line() else {
+                if self.comment_depth > 0 {
+                    self.comment_depth = 0;
+                    self.errors.push(LexError {
+                        kind : LexErrorKind::UnterminatedComment,
+                        span : self.comment_start.clone(),
+                    });
+                }
+                self.eof_read = true;
+                return
+            };
+            self.lnum += 1;
             let lbuf = match line {
-                Err(e) => panic!("Problem reading file on line {:?}: {:?}", self.lnum, e),
+                Err(e) => {
+                    // Invalid UTF-8 only spoils the current line, but other errors mean the rest
+                    // of the input can't be trusted
+                    if e.kind() != ErrorKind::InvalidData {
+                        self.eof_read = true;
+                    }
+                    let span = self.span_from(1, 0, &LineCursor::new(""));
+                    self.errors.push(LexError {
+                        kind : LexErrorKind::Io(e.to_string()),
+                        span : span.clone(),
+                    });
+                    self.tokens.push_back((Token::INVAL, span));
+                    self.line_start += len;
+                    continue;
+                },
                 Ok(l) => l,
             };
-            let mut lpeek = lbuf.chars().peekable();
+            let mut lpeek = LineCursor::new(&lbuf);
             loop {
                 match self.read_token(&mut lpeek) {
-                    Token::EOL => break, // For now, we just ignore end of line tokens.
+                    (Token::EOL, _) => break, // For now, we just ignore end of line tokens.
                     other => self.tokens.push_back(other),
                 }
             }
+            self.line_start += len;
         }
-        return self.tokens.pop_front()
     }
-}
 
-impl<T : Read> Scanner<T> {
-    pub fn new(input : T) -> Self {
-        Self {
-            tokens : VecDeque::new(),
-            lnum : 0,
-            lines : BufReader::new(input).lines(),
+    // Read the next line of input without its line terminator, along with the number of bytes
+    // read including the terminator. None once the input runs out
+    fn read_line(&mut self) -> Option<(io::Result<String>, usize)> {
+        let mut buf = Vec::new();
+        if let Err(e) = self.input.read_until(b'\n', &mut buf) {
+            return Some((Err(e), buf.len()))
+        }
+        if buf.is_empty() {
+            return None
         }
+        let len = buf.len();
+        // Lines end in either \n or \r\n
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+        }
+        let line = String::from_utf8(buf).map_err(|_| io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8"));
+        Some((line, len))
     }
 
-    // Check next token without advancing
-    pub fn peek(&mut self) -> Option<Token> {
-        if let Some(t) = self.next()  {
-            // Don't actually consume an element
-            self.tokens.push_front(t.clone());
-            return Some(t);
-        } else {
-            return None;
+    // Create a span on the current line from the start position up to the current position of the
+    // reader
+    fn span_from(&self, start_col : u64, start_byte : usize, reader : &LineCursor) -> Span {
+        Span {
+            file : self.file.clone(),
+            start_line : self.lnum,
+            start_col : start_col,
+            end_line : self.lnum,
+            end_col : reader.col,
+            byte_range : self.line_start + start_byte..self.line_start + reader.byte,
         }
     }
 
@@ -62,46 +200,271 @@ impl<T : Read> Scanner<T> {
     fn match_keyword(&self, buffer : &str) -> Token {
         match buffer {
             "while" => Token::While,
+            "for" => Token::For,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
             "if" => Token::If,
             "else" => Token::Else,
             "int" => Token::IntKey,
             "char" => Token::CharKey,
+            "long" => Token::LongKey,
+            "short" => Token::ShortKey,
+            "unsigned" => Token::UnsignedKey,
+            "bool" => Token::BoolKey,
+            "float" => Token::FloatKey,
+            "double" => Token::DoubleKey,
+            "struct" => Token::StructKey,
+            "enum" => Token::EnumKey,
+            "match" => Token::Match,
+            "switch" => Token::Switch,
+            "case" => Token::Case,
+            "default" => Token::Default,
+            "typedef" => Token::Typedef,
+            "type" => Token::TypeKey,
+            "sizeof" => Token::Sizeof,
+            "true" => Token::BoolConst(true),
+            "false" => Token::BoolConst(false),
+            "const" => Token::Const,
+            "static" => Token::Static,
+            "volatile" => Token::Volatile,
+            "pub" => Token::Pub,
+            "import" => Token::Import,
             "return" => Token::Ret,
             _ => Token::Id(buffer.to_string()),
         }
     }
 
-    // For now, just support base 10 ints
-    fn read_int_const(&self, reader : &mut Peekable<Chars>, buffer : &mut String) -> Token {
+    // Read an integer constant. Supports 0x, 0o (or just a leading 0) and 0b radix prefixes, _ digit
+    // separators, and u and l suffixes. Malformed constants are reported, but are still returned as
+    // an IntConst so that parsing is unaffected. Decimal constants with a fraction or exponent are
+    // read as floating point constants instead
+    fn read_int_const(&mut self, reader : &mut LineCursor, buffer : &mut String) -> Token {
+        let mut radix = 10;
+        let mut prefixed = false;
+        if buffer == "0" {
+            match reader.peek() {
+                Some('x' | 'X') => radix = 16,
+                Some('o' | 'O') => radix = 8,
+                Some('b' | 'B') => radix = 2,
+                _ => (),
+            }
+            if radix != 10 {
+                // Drop the leading 0 and the prefix letter
+                reader.next();
+                buffer.clear();
+                prefixed = true;
+            } else if reader.peek().is_some_and(|x| x.is_ascii_digit() || *x == '_') {
+                // C-style octal constant, with just a leading 0
+                radix = 8;
+            }
+        }
+        // Take every digit, even if it is invalid in this radix, so the error can be reported
         while let Some(x) = reader.peek() {
-            if !x.is_ascii_digit() {
+            if !(x.is_ascii_digit() || (radix == 16 && x.is_ascii_hexdigit()) || *x == '_') {
                 break;
             }
             buffer.push(*x);
             reader.next();
         }
-        Token::IntConst(buffer.parse().expect("IntConst formatted incorrectly"))
+        if !prefixed && starts_float_tail(reader) {
+            return self.read_float_const(reader, buffer);
+        }
+        let mut suffix_buf = String::new();
+        while let Some(x) = reader.peek() {
+            if !(x.is_ascii_alphanumeric() || *x == '_') {
+                break;
+            }
+            suffix_buf.push(*x);
+            reader.next();
+        }
+        let suffix = match suffix_buf.to_ascii_lowercase().as_str() {
+            "" => IntSuffix::None,
+            "u" => IntSuffix::U,
+            "l" => IntSuffix::L,
+            "ul" | "lu" => IntSuffix::UL,
+            _ => {
+                self.token_errors.push(LexErrorKind::InvalidIntSuffix(suffix_buf));
+                IntSuffix::None
+            },
+        };
+        buffer.retain(|x| x != '_');
+        if buffer.is_empty() {
+            self.token_errors.push(LexErrorKind::MissingDigits);
+            return Token::IntConst(0, suffix, radix);
+        }
+        if let Some(x) = buffer.chars().find(|x| !x.is_digit(radix)) {
+            self.token_errors.push(LexErrorKind::InvalidDigit(x, radix));
+            return Token::IntConst(0, suffix, radix);
+        }
+        match u64::from_str_radix(buffer, radix) {
+            Ok(val) if val <= suffix.max(radix) => Token::IntConst(val, suffix, radix),
+            _ => {
+                self.token_errors.push(LexErrorKind::IntOutOfRange { suffix : suffix, radix : radix });
+                Token::IntConst(0, suffix, radix)
+            },
+        }
     }
 
-    // Consume a string
-    fn read_str_const(&self, reader : &mut Peekable<Chars>, buffer : &mut String) -> Token {
+    // Read the fraction, exponent and suffix of a floating point constant, given its integer part.
+    // Malformed constants are reported, but are still returned as a FloatConst so that parsing is
+    // unaffected
+    fn read_float_const(&mut self, reader : &mut LineCursor, buffer : &mut String) -> Token {
+        if let Some('.') = reader.peek() {
+            buffer.push('.');
+            reader.next();
+            while let Some(x) = reader.peek().filter(|x| x.is_ascii_digit() || **x == '_') {
+                buffer.push(*x);
+                reader.next();
+            }
+        }
+        if let Some('e' | 'E') = reader.peek() {
+            buffer.push('e');
+            reader.next();
+            if let Some(x) = reader.peek().filter(|x| **x == '+' || **x == '-') {
+                buffer.push(*x);
+                reader.next();
+            }
+            let mut has_digits = false;
+            while let Some(x) = reader.peek().filter(|x| x.is_ascii_digit() || **x == '_') {
+                has_digits |= x.is_ascii_digit();
+                buffer.push(*x);
+                reader.next();
+            }
+            if !has_digits {
+                self.token_errors.push(LexErrorKind::MissingExponent);
+                buffer.push('0');
+            }
+        }
+        let mut suffix_buf = String::new();
+        while let Some(x) = reader.peek() {
+            if !(x.is_ascii_alphanumeric() || *x == '_') {
+                break;
+            }
+            suffix_buf.push(*x);
+            reader.next();
+        }
+        let suffix = match suffix_buf.to_ascii_lowercase().as_str() {
+            "" => FloatSuffix::None,
+            "f" => FloatSuffix::F,
+            _ => {
+                self.token_errors.push(LexErrorKind::InvalidFloatSuffix(suffix_buf));
+                FloatSuffix::None
+            },
+        };
+        buffer.retain(|x| x != '_');
+        let val : f64 = buffer.parse().expect("Checked float syntax");
+        let too_large = match suffix {
+            FloatSuffix::None => val.is_infinite(),
+            FloatSuffix::F => (val as f32).is_infinite(),
+        };
+        if too_large {
+            self.token_errors.push(LexErrorKind::FloatOutOfRange);
+            return Token::FloatConst(FloatVal::new(0.0), suffix);
+        }
+        Token::FloatConst(FloatVal::new(val), suffix)
+    }
+
+    // Record an error in the token currently being read, and return the token which replaces it
+    fn invalid(&mut self, kind : LexErrorKind) -> Token {
+        self.token_errors.push(kind);
+        Token::INVAL
+    }
+
+    // Decode an escape sequence. The backslash should have already been consumed. Returns None if
+    // the line ends before the escape does. Malformed escapes are reported, and decode to the
+    // character following the backslash so that the rest of the literal can still be read
+    fn read_escape(&mut self, reader : &mut LineCursor) -> Option<char> {
+        let x = reader.next()?;
+        let c = match x {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' | '\'' | '"' => x,
+            // Exactly 2 hex digits. The digits of a malformed escape are skipped along with it
+            'x' => {
+                let digits : String = reader.clone().take(2).take_while(|c| c.is_ascii_hexdigit()).collect();
+                reader.by_ref().take(digits.len()).for_each(drop);
+                if digits.len() != 2 {
+                    self.token_errors.push(LexErrorKind::BadHexEscape);
+                    return Some(x);
+                }
+                char::from(u8::from_str_radix(&digits, 16).expect("Checked hex digits"))
+            },
+            // 1 to 6 hex digits in braces, which must form a unicode scalar value
+            'u' => {
+                if reader.peek() != Some(&'{') {
+                    self.token_errors.push(LexErrorKind::BadUnicodeEscape);
+                    return Some(x);
+                }
+                let mut ahead = reader.clone();
+                ahead.next();
+                let digits : String = ahead.by_ref().take_while(|c| *c != '}' && *c != '"' && *c != '\'').collect();
+                let c = u32::from_str_radix(&digits, 16).ok()
+                    .filter(|_| digits.len() <= 6)
+                    .and_then(char::from_u32);
+                // Skip the brace, digits and closing brace even if the escape is malformed, so the
+                // rest of it isn't read as characters
+                reader.by_ref().take(digits.len() + 1).for_each(drop);
+                let closed = reader.peek() == Some(&'}');
+                if closed {
+                    reader.next();
+                }
+                let Some(c) = c.filter(|_| closed) else {
+                    self.token_errors.push(LexErrorKind::BadUnicodeEscape);
+                    return Some(x);
+                };
+                c
+            },
+            other => {
+                self.token_errors.push(LexErrorKind::UnknownEscape(other));
+                other
+            },
+        };
+        Some(c)
+    }
+
+    // Consume a string. Strings may not span multiple lines
+    fn read_str_const(&mut self, reader : &mut LineCursor, buffer : &mut String) -> Token {
         while let Some(x) = reader.next() {
-            if x == '"' {
-                return Token::StrConst(buffer.clone());
+            match x {
+                '"' => return Token::StrConst(buffer.clone()),
+                '\\' => match self.read_escape(reader) {
+                    Some(c) => buffer.push(c),
+                    None => break,
+                },
+                _ => buffer.push(x),
             }
-            buffer.push(x);
         }
-        panic!("No matching double quote to end string constant starting on line {0}", self.lnum)
+        self.invalid(LexErrorKind::UnterminatedStr)
     }
 
     // Consume a single character
-    fn read_char_const(&self, reader : &mut Peekable<Chars>) -> Token {
-        let Some(x) = reader.next() else {panic!("Unexpected single quote at end of program")};
-        let Some('\'') = reader.next() else {panic!("No matching single quote to close char on line {0}", self.lnum)};
-        Token::CharConst(x)
+    fn read_char_const(&mut self, reader : &mut LineCursor) -> Token {
+        let x = match reader.next() {
+            None => return self.invalid(LexErrorKind::UnterminatedChar),
+            Some('\'') => return self.invalid(LexErrorKind::EmptyChar),
+            Some('\\') => match self.read_escape(reader) {
+                Some(c) => c,
+                None => return self.invalid(LexErrorKind::UnterminatedChar),
+            },
+            Some(x) => x,
+        };
+        if let Some('\'') = reader.peek() {
+            reader.next();
+            return Token::CharConst(x);
+        }
+        // Resynchronise at the closing quote if there is one on this line. Otherwise, only the
+        // opening quote and the character after it are treated as part of the bad token
+        if reader.clone().any(|c| c == '\'') {
+            while reader.next() != Some('\'') {}
+            self.invalid(LexErrorKind::OversizedChar)
+        } else {
+            self.invalid(LexErrorKind::UnterminatedChar)
+        }
     }
 
-    fn read_id_or_key(&self, reader : &mut Peekable<Chars>, buffer : &mut String) -> Token {
+    fn read_id_or_key(&self, reader : &mut LineCursor, buffer : &mut String) -> Token {
         // Lookahead to only consume characters which can go in an identifier
         while let Some(x) = reader.peek() {
             if !(x.is_ascii_alphanumeric() || *x == '_') {
@@ -113,7 +476,33 @@ impl<T : Read> Scanner<T> {
         self.match_keyword(buffer)
     }
 
-    fn read_token(&self, reader : &mut Peekable<Chars>) -> Token {
+    // Consume the body of a block comment, which may contain nested block comments, up to and
+    // including the close of the outermost comment. Stops at the end of the line if the comment
+    // continues past it.
+    fn skip_block_comment(&mut self, reader : &mut LineCursor) {
+        while let Some(x) = reader.next() {
+            match (x, reader.peek()) {
+                ('*', Some('/')) => {
+                    reader.next();
+                    self.comment_depth -= 1;
+                    if self.comment_depth == 0 {
+                        return;
+                    }
+                },
+                ('/', Some('*')) => {
+                    reader.next();
+                    self.comment_depth += 1;
+                },
+                _ => (),
+            }
+        }
+    }
+
+    fn read_token(&mut self, reader : &mut LineCursor) -> (Token, Span) {
+        // Finish a block comment carried over from a previous line
+        if self.comment_depth > 0 {
+            self.skip_block_comment(reader);
+        }
         // Strip whitespace from the iterator
         while let Some(x) = reader.peek() {
             if !x.is_whitespace() {
@@ -121,6 +510,11 @@ impl<T : Read> Scanner<T> {
             } 
             reader.next();
         }
+        let (start_col, start_byte) = (reader.col, reader.byte);
+        // Still inside a block comment, so this line has been fully consumed
+        if self.comment_depth > 0 {
+            return (Token::EOL, self.span_from(start_col, start_byte, reader))
+        }
         // Simple 2 layer switch case to read character by character
         let tok : Token = match reader.next() {
             None => Token::EOL,
@@ -134,6 +528,9 @@ impl<T : Read> Scanner<T> {
                 ';' => Token::Semi,
                 ':' => Token::Colon,
                 ',' => Token::Comma,
+                '?' => Token::Question,
+                '.' => Token::Dot,
+                '~' => Token::Tilde,
                 '"' => self.read_str_const(reader, &mut "".to_string()),
                 '\'' => self.read_char_const(reader),
                 '+' => match reader.peek() {
@@ -156,6 +553,10 @@ impl<T : Read> Scanner<T> {
                         reader.next();
                         Token::SubAss
                     }
+                    Some('>') => {
+                        reader.next();
+                        Token::Arrow
+                    }
                     _ => Token::Sub,
                 },
                 '*' => match reader.peek() {
@@ -170,13 +571,65 @@ impl<T : Read> Scanner<T> {
                         reader.next();
                         Token::DivAss
                     }
+                    // Line comment, ignore the rest of the line
+                    Some('/') => {
+                        for _ in reader.by_ref() {}
+                        Token::EOL
+                    }
+                    // Block comment, skip past it and read the token after it instead
+                    Some('*') => {
+                        reader.next();
+                        self.comment_depth = 1;
+                        self.comment_start = self.span_from(start_col, start_byte, reader);
+                        return self.read_token(reader)
+                    }
                     _ => Token::Div,
                 },
+                '%' => match reader.peek() {
+                    Some('=') => {
+                        reader.next();
+                        Token::ModAss
+                    }
+                    _ => Token::Mod,
+                },
+                '&' => match reader.peek() {
+                    Some('&') => {
+                        reader.next();
+                        Token::And
+                    }
+                    Some('=') => {
+                        reader.next();
+                        Token::AndAss
+                    }
+                    _ => Token::Amp,
+                },
+                '|' => match reader.peek() {
+                    Some('|') => {
+                        reader.next();
+                        Token::Or
+                    }
+                    Some('=') => {
+                        reader.next();
+                        Token::OrAss
+                    }
+                    _ => Token::Pipe,
+                },
+                '^' => match reader.peek() {
+                    Some('=') => {
+                        reader.next();
+                        Token::XorAss
+                    }
+                    _ => Token::Caret,
+                },
                 '=' => match reader.peek() {
                     Some('=') => {
                         reader.next();
                         Token::Equal
                     }
+                    Some('>') => {
+                        reader.next();
+                        Token::FatArrow
+                    }
                     _ => Token::Assign,
                 },
                 '!' => match reader.peek() {
@@ -191,6 +644,15 @@ impl<T : Read> Scanner<T> {
                         reader.next();
                         Token::GE
                     }
+                    Some('>') => {
+                        reader.next();
+                        if let Some('=') = reader.peek() {
+                            reader.next();
+                            Token::ShrAss
+                        } else {
+                            Token::Shr
+                        }
+                    }
                     _ => Token::GT,
                 },
                 '<' => match reader.peek() {
@@ -198,20 +660,159 @@ impl<T : Read> Scanner<T> {
                         reader.next();
                         Token::LE
                     }
+                    Some('<') => {
+                        reader.next();
+                        if let Some('=') = reader.peek() {
+                            reader.next();
+                            Token::ShlAss
+                        } else {
+                            Token::Shl
+                        }
+                    }
                     _ => Token::LT,
                 },
                 other => {
                     if other.is_ascii_alphabetic() || other == '_' {
                         self.read_id_or_key(reader, &mut other.to_string())
                     } else if other.is_ascii_digit() {
-                        self.read_int_const(reader,  &mut other.to_string())
+                        self.read_int_const(reader, &mut other.to_string())
                     } else {
-                        panic!("unrecognized token {other:?}")
+                        self.invalid(LexErrorKind::UnrecognizedChar(other))
                     }
                 },
             }
         };
-        tok
+        let span = self.span_from(start_col, start_byte, reader);
+        for kind in self.token_errors.drain(..) {
+            self.errors.push(LexError { kind : kind, span : span.clone() });
+        }
+        (tok, span)
     }
 
 }
+
+// Check if the rest of a decimal constant is the fraction or exponent of a floating point constant.
+// An e only starts an exponent if it is followed by a digit or sign
+fn starts_float_tail(reader : &LineCursor) -> bool {
+    let mut ahead = reader.clone();
+    match ahead.next() {
+        Some('.') => true,
+        Some('e' | 'E') => matches!(ahead.next(), Some('0'..='9' | '+' | '-')),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Return the tokens of source along with their spans, and the lexical errors found
+    fn scan(source : &str) -> (Vec<(Token, Span)>, Vec<LexErrorKind>) {
+        let mut scanner = Scanner::new(source.as_bytes(), "test.c");
+        let tokens = std::iter::from_fn(|| scanner.next_spanned()).collect();
+        (tokens, scanner.errors().iter().map(|e| e.kind.clone()).collect())
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let (tokens, errors) = scan("a // b /* c\nd /* e /* f */ g */ h / i");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        let id = |s : &str| Token::Id(s.to_string());
+        assert_eq!(tokens, [id("a"), id("d"), id("h"), Token::Div, id("i")]);
+        assert!(errors.is_empty());
+        let (tokens, errors) = scan("a /* b /* c */");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(errors, [LexErrorKind::UnterminatedComment]);
+    }
+
+    #[test]
+    fn lexical_errors_are_recorded_and_scanning_continues() {
+        let (tokens, errors) = scan("a @ b '' 'xy' c\n\"open");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        let id = |s : &str| Token::Id(s.to_string());
+        assert_eq!(tokens, [id("a"), Token::INVAL, id("b"), Token::INVAL, Token::INVAL, id("c"), Token::INVAL]);
+        assert_eq!(errors, [
+            LexErrorKind::UnrecognizedChar('@'),
+            LexErrorKind::EmptyChar,
+            LexErrorKind::OversizedChar,
+            LexErrorKind::UnterminatedStr,
+        ]);
+    }
+
+    #[test]
+    fn escapes_are_decoded() {
+        let (tokens, errors) = scan("'\\x41' '\\u{e9}' \"a\\tb\\\"\"");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        assert_eq!(tokens, [Token::CharConst('A'), Token::CharConst('é'), Token::StrConst("a\tb\"".to_string())]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn integer_constants_take_radix_prefixes_separators_and_suffixes() {
+        let (tokens, errors) = scan("0x1F 0o17 017 0b101 1_000 10u 10L 10ul");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        let int = Token::IntConst;
+        assert_eq!(tokens, [
+            int(31, IntSuffix::None, 16),
+            int(15, IntSuffix::None, 8),
+            int(15, IntSuffix::None, 8),
+            int(5, IntSuffix::None, 2),
+            int(1000, IntSuffix::None, 10),
+            int(10, IntSuffix::U, 10),
+            int(10, IntSuffix::L, 10),
+            int(10, IntSuffix::UL, 10),
+        ]);
+        assert!(errors.is_empty(), "{errors:?}");
+        let (_, errors) = scan("0b102 0x 10z");
+        assert_eq!(errors, [LexErrorKind::InvalidDigit('2', 2), LexErrorKind::MissingDigits, LexErrorKind::InvalidIntSuffix("z".to_string())]);
+    }
+
+    #[test]
+    fn crlf_line_endings_advance_byte_offsets() {
+        let (tokens, _) = scan("a\r\nbc\r\nd");
+        let ranges : Vec<_> = tokens.iter().map(|(_, span)| span.byte_range.clone()).collect();
+        assert_eq!(ranges, [0..1, 3..5, 7..8]);
+        assert_eq!((tokens[2].1.start_line, tokens[2].1.start_col), (3, 1));
+    }
+
+    #[test]
+    fn malformed_escape_in_char_is_one_error() {
+        for (source, kind) in [
+            ("'\\u{110000}'", LexErrorKind::BadUnicodeEscape),
+            ("'\\u{12'", LexErrorKind::BadUnicodeEscape),
+            ("'\\x4'", LexErrorKind::BadHexEscape),
+            ("'\\q'", LexErrorKind::UnknownEscape('q')),
+        ] {
+            let (tokens, errors) = scan(source);
+            assert_eq!(errors, [kind], "{source}");
+            assert_eq!(tokens.len(), 1, "{source}");
+        }
+    }
+
+    #[test]
+    fn unsuffixed_decimal_constants_can_be_long() {
+        let (tokens, errors) = scan("2147483648 9223372036854775807");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        assert_eq!(tokens, [Token::IntConst(2147483648, IntSuffix::None, 10), Token::IntConst(i64::MAX as u64, IntSuffix::None, 10)]);
+        assert!(errors.is_empty());
+        let (_, errors) = scan("9223372036854775808 4294967296u");
+        assert_eq!(errors, [
+            LexErrorKind::IntOutOfRange { suffix : IntSuffix::None, radix : 10 },
+            LexErrorKind::IntOutOfRange { suffix : IntSuffix::U, radix : 10 },
+        ]);
+    }
+
+    #[test]
+    fn unsuffixed_constants_in_other_radixes_can_be_unsigned() {
+        let (tokens, errors) = scan("0x80000000 0xFFFFFFFF 0o1777777777777777777777");
+        let tokens : Vec<_> = tokens.into_iter().map(|(tok, _)| tok).collect();
+        assert_eq!(tokens, [
+            Token::IntConst(0x80000000, IntSuffix::None, 16),
+            Token::IntConst(u32::MAX as u64, IntSuffix::None, 16),
+            Token::IntConst(u64::MAX, IntSuffix::None, 8),
+        ]);
+        assert!(errors.is_empty(), "{errors:?}");
+        let (_, errors) = scan("0x10000000000000000");
+        assert_eq!(errors, [LexErrorKind::IntOutOfRange { suffix : IntSuffix::None, radix : 16 }]);
+    }
+}
@@ -0,0 +1,39 @@
+use std::{fmt, ops::Range, rc::Rc};
+
+// A region of source text. Lines and columns are 1-based and inclusive of the first character,
+// while the end column and byte range are exclusive of the last character (half open), so a span
+// over a single character x on line 1 is 1:1-1:2
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub file : Rc<str>,
+    pub start_line : u64,
+    pub start_col : u64,
+    pub end_line : u64,
+    pub end_col : u64,
+    pub byte_range : Range<usize>,
+}
+
+impl Span {
+    // Return the smallest span which covers both self and other. Both spans are assumed to be from
+    // the same file.
+    pub fn to(&self, other : &Span) -> Span {
+        let (start, end) = (
+            if (self.start_line, self.start_col) <= (other.start_line, other.start_col) {self} else {other},
+            if (self.end_line, self.end_col) >= (other.end_line, other.end_col) {self} else {other},
+        );
+        Span {
+            file : self.file.clone(),
+            start_line : start.start_line,
+            start_col : start.start_col,
+            end_line : end.end_line,
+            end_col : end.end_col,
+            byte_range : start.byte_range.start..end.byte_range.end,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.start_line, self.start_col)
+    }
+}
@@ -1,18 +1,53 @@
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Token {
     // Meta Control Tokens
     EOL, // End of line
+    #[allow(dead_code)] // The scanner returns None at the end of the file instead
     EOF, // End of line
     INVAL, // Invalid token
     // Keywords
     While,
+    For,
+    Break,
+    Continue,
     If,
     Else,
     IntKey,
     CharKey,
+    LongKey,
+    ShortKey,
+    UnsignedKey,
+    BoolKey,
+    FloatKey,
+    DoubleKey,
+    StructKey,
+    StructType(String), // struct NAME as a type specifier. Produced by the parser, not the scanner
+    EnumKey,
+    EnumType(String), // enum NAME as a type specifier. Produced by the parser, not the scanner
+    Match,
+    Switch,
+    Case,
+    Default,
+    Typedef,
+    TypeKey,
+    Sizeof,
+    TypeName(String), // A name declared as a type alias. Produced by the parser, not the scanner
+    // RET(PARAMS) as a type specifier, given the specifiers of the return type and of each
+    // parameter type, which is followed by a Star for each pointer. Produced by the parser
+    FunctType(Vec<Token>, Vec<Vec<Token>>),
+    Const,
+    Static,
+    Volatile,
+    Pub,
+    Import,
     Ret,
     // Value tokens
-    IntConst(i32),
+    IntConst(u64, IntSuffix, u32), // Value, suffix and radix
+    FloatConst(FloatVal, FloatSuffix),
+    BoolConst(bool),
     CharConst(char),
     StrConst(String),
     Id(String),
@@ -26,6 +61,10 @@ pub enum Token {
     Semi, // ;
     Colon, // :
     Comma, // ,
+    Question, // ?
+    Dot, // .
+    Arrow, // ->
+    FatArrow, // =>
 
     Add, // +
     Inc, // ++
@@ -41,6 +80,22 @@ pub enum Token {
     Div, // /
     DivAss, // /=
 
+    Mod, // %
+    ModAss, // %=
+
+    Amp, // &
+    AndAss, // &=
+    And, // &&
+
+    Pipe, // |
+    OrAss, // |=
+    Or, // ||
+
+    Caret, // ^
+    XorAss, // ^=
+
+    Tilde, // ~
+
 
     Assign, // =
     Equal, // ==
@@ -50,7 +105,107 @@ pub enum Token {
 
     GT, // >
     GE, // >=
+    Shr, // >>
+    ShrAss, // >>=
 
     LT, // <
     LE, // <=
+    Shl, // <<
+    ShlAss, // <<=
+}
+
+impl Token {
+    // Check if the token names a type, and so can begin a declaration
+    pub fn is_type_spec(&self) -> bool {
+        matches!(self,
+            Token::IntKey
+            | Token::CharKey
+            | Token::LongKey
+            | Token::ShortKey
+            | Token::UnsignedKey
+            | Token::BoolKey
+            | Token::FloatKey
+            | Token::DoubleKey
+            | Token::StructType(_)
+            | Token::EnumType(_)
+            | Token::TypeName(_)
+            | Token::FunctType(..))
+    }
+
+    // Check if the token modifies an existing variable
+    pub fn is_modifier(&self) -> bool {
+        matches!(self, Token::Const | Token::Static | Token::Volatile | Token::Pub)
+    }
+}
+
+// The suffix of an integer constant, which determines the range of values it can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntSuffix {
+    None,
+    U, // Unsigned
+    L, // Long
+    UL, // Unsigned long
+}
+
+impl IntSuffix {
+    // Return the largest constant in the given radix which can have this suffix. As in C, an
+    // unsuffixed decimal constant too large for an int is a long, while other radixes also try
+    // unsigned int before long, and unsigned long after it
+    pub fn max(&self, radix : u32) -> u64 {
+        match self {
+            IntSuffix::None if radix == 10 => i64::MAX as u64,
+            IntSuffix::None => u64::MAX,
+            IntSuffix::U => u32::MAX as u64,
+            IntSuffix::L => i64::MAX as u64,
+            IntSuffix::UL => u64::MAX,
+        }
+    }
+}
+
+impl fmt::Display for IntSuffix {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntSuffix::None => Ok(()),
+            IntSuffix::U => write!(f, "u"),
+            IntSuffix::L => write!(f, "l"),
+            IntSuffix::UL => write!(f, "ul"),
+        }
+    }
+}
+
+// The value of a floating point constant. Stored as its bits so that tokens can be compared and
+// hashed
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FloatVal(u64);
+
+impl FloatVal {
+    pub fn new(val : f64) -> Self {
+        FloatVal(val.to_bits())
+    }
+
+    pub fn val(&self) -> f64 {
+        f64::from_bits(self.0)
+    }
+}
+
+impl fmt::Debug for FloatVal {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.val())
+    }
+}
+
+// The suffix of a floating point constant. Constants are doubles unless they have an f suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatSuffix {
+    None,
+    F, // Float
+}
+
+impl fmt::Display for FloatSuffix {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloatSuffix::None => Ok(()),
+            FloatSuffix::F => write!(f, "f"),
+        }
+    }
 }